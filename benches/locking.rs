@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const OPS_PER_THREAD: usize = 1_000;
+
+fn contended_std(threads: usize) {
+    let map = Arc::new(std::sync::Mutex::new(HashMap::new()));
+    let handles: Vec<_> = (0..threads)
+        .map(|t| {
+            let map = Arc::clone(&map);
+            thread::spawn(move || {
+                for i in 0..OPS_PER_THREAD {
+                    let mut map = map.lock().unwrap();
+                    map.insert(t * OPS_PER_THREAD + i, i);
+                    let _ = map.get(&i);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+fn contended_parking_lot(threads: usize) {
+    let map = Arc::new(parking_lot::Mutex::new(HashMap::new()));
+    let handles: Vec<_> = (0..threads)
+        .map(|t| {
+            let map = Arc::clone(&map);
+            thread::spawn(move || {
+                for i in 0..OPS_PER_THREAD {
+                    let mut map = map.lock();
+                    map.insert(t * OPS_PER_THREAD + i, i);
+                    let _ = map.get(&i);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+fn bench_locking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contended_lock");
+    for threads in [1, 4, 8] {
+        group.bench_with_input(BenchmarkId::new("std", threads), &threads, |b, &t| {
+            b.iter(|| contended_std(t))
+        });
+        group.bench_with_input(BenchmarkId::new("parking_lot", threads), &threads, |b, &t| {
+            b.iter(|| contended_parking_lot(t))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_locking);
+criterion_main!(benches);
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rustycache::rustycache::Rustycache;
+use rustycache::strategy::StrategyType;
+
+const STRATEGIES: [(&str, StrategyType); 3] = [
+    ("lru", StrategyType::LRU),
+    ("fifo", StrategyType::FIFO),
+    ("lfu", StrategyType::LFU),
+];
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+fn create_cache(capacity: usize, strategy: StrategyType) -> Rustycache<u64, u64> {
+    Rustycache::new(capacity, Duration::from_secs(600), Duration::from_secs(600), strategy)
+}
+
+fn filled_cache(capacity: usize, strategy: StrategyType) -> Rustycache<u64, u64> {
+    let mut cache = create_cache(capacity, strategy);
+    for i in 0..capacity as u64 {
+        cache.insert(i, i);
+    }
+    cache
+}
+
+fn bench_put(c: &mut Criterion) {
+    let mut group = c.benchmark_group("put");
+    for (name, strategy) in STRATEGIES {
+        for size in SIZES {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                let mut cache = create_cache(size, strategy);
+                let mut i = 0u64;
+                b.iter(|| {
+                    cache.insert(i % size as u64, i);
+                    i += 1;
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_get_hit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_hit");
+    for (name, strategy) in STRATEGIES {
+        for size in SIZES {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                let mut cache = filled_cache(size, strategy);
+                let mut i = 0u64;
+                b.iter(|| {
+                    let value = cache.get(&(i % size as u64));
+                    i += 1;
+                    value
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_get_hit_string(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_hit_string");
+    for (name, strategy) in STRATEGIES {
+        for size in SIZES {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                let mut cache = Rustycache::new(size, Duration::from_secs(600), Duration::from_secs(600), strategy);
+                let keys: Vec<String> = (0..size).map(|i| format!("user:{i}")).collect();
+                for key in &keys {
+                    cache.insert(key.clone(), 0u64);
+                }
+                let mut i = 0;
+                b.iter(|| {
+                    let value = cache.get(&keys[i % size]);
+                    i += 1;
+                    value
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_get_miss(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_miss");
+    for (name, strategy) in STRATEGIES {
+        for size in SIZES {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                let mut cache = filled_cache(size, strategy);
+                let mut i = size as u64;
+                b.iter(|| {
+                    let value = cache.get(&i);
+                    i += 1;
+                    value
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_eviction_heavy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eviction_heavy");
+    for (name, strategy) in STRATEGIES {
+        for size in SIZES {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                let mut cache = filled_cache(size, strategy);
+                let mut i = size as u64;
+                b.iter(|| {
+                    cache.insert(i, i); // always a new key, so every put evicts
+                    i += 1;
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_remove_absent(c: &mut Criterion) {
+    let mut group = c.benchmark_group("remove_absent");
+    for (name, strategy) in STRATEGIES {
+        for size in SIZES {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                let mut cache = filled_cache(size, strategy);
+                let mut i = size as u64;
+                b.iter(|| {
+                    cache.remove(&i);
+                    i += 1;
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _guard = runtime.enter();
+
+    bench_put(c);
+    bench_get_hit(c);
+    bench_get_hit_string(c);
+    bench_get_miss(c);
+    bench_eviction_heavy(c);
+    bench_remove_absent(c);
+}
+
+criterion_group!(strategy_benches, benches);
+criterion_main!(strategy_benches);
@@ -0,0 +1,24 @@
+//! Serves a `Rustycache<String, String>` over TCP; see `rustycache::server`.
+//!
+//! Usage: `rustycache-server [addr] [capacity] [ttl_secs]`, defaulting to
+//! `127.0.0.1:6380`, 10000 entries and 300 seconds.
+
+use std::time::Duration;
+
+use rustycache::rustycache::Rustycache;
+use rustycache::server;
+use rustycache::strategy::StrategyType;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:6380".to_string());
+    let capacity = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(10_000);
+    let ttl = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(300);
+
+    let cache = Rustycache::new(capacity, Duration::from_secs(ttl), Duration::from_secs(10), StrategyType::LRU);
+    let listener = TcpListener::bind(&addr).await?;
+    println!("rustycache-server listening on {}", listener.local_addr()?);
+    server::serve(listener, cache).await
+}
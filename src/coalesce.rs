@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task;
+use tokio::time::sleep;
+
+use crate::spillover::{put_spilling, Spill};
+use crate::strategy::CacheStrategy;
+use crate::sync::Mutex;
+use crate::write_behind::WriteBehind;
+
+/// Buffers puts so that repeated writes to one key within `window` reach the
+/// cache as a single put of the last value. The first put to a key starts a
+/// timer; later puts only replace the buffered value.
+pub(crate) struct PutCoalescer<K, V> {
+    window: Duration,
+    pending: Arc<Mutex<HashMap<K, V>>>,
+}
+
+impl<K, V> PutCoalescer<K, V>
+where
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Send + Sync + 'static,
+{
+    pub(crate) fn new(window: Duration) -> Self {
+        PutCoalescer { window, pending: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// Capacity victims of the eventual put go to `spillover`, and the
+    /// stored value to `write_behind`, if given.
+    pub(crate) fn stash(
+        &self,
+        key: K,
+        value: V,
+        inner: &Arc<dyn CacheStrategy<K, V>>,
+        spillover: Option<&Arc<dyn Spill<K, V>>>,
+        write_behind: Option<&Arc<WriteBehind<K, V>>>,
+    ) {
+        let first = self.pending.lock().insert(key.clone(), value).is_none();
+        if !first {
+            return;
+        }
+        let pending = Arc::clone(&self.pending);
+        let inner = Arc::clone(inner);
+        let spillover = spillover.cloned();
+        let write_behind = write_behind.cloned();
+        let window = self.window;
+        task::spawn(async move {
+            sleep(window).await;
+            let value = pending.lock().remove(&key);
+            if let Some(value) = value {
+                land(&*inner, spillover.as_deref(), write_behind.as_deref(), key, value);
+            }
+        });
+    }
+
+    /// Drops the buffered value for `key`, so a pending flush can't bring
+    /// back a key that was removed in the meantime.
+    pub(crate) fn discard(&self, key: &K) {
+        self.pending.lock().remove(key);
+    }
+
+    pub(crate) fn discard_all(&self) {
+        self.pending.lock().clear();
+    }
+
+    /// Applies every buffered value now instead of waiting for its timer.
+    pub(crate) fn apply_all(&self, inner: &dyn CacheStrategy<K, V>, spillover: Option<&dyn Spill<K, V>>, write_behind: Option<&WriteBehind<K, V>>) {
+        let pending: Vec<(K, V)> = self.pending.lock().drain().collect();
+        for (key, value) in pending {
+            land(inner, spillover, write_behind, key, value);
+        }
+    }
+}
+
+/// Puts a buffered value and forwards it to `write_behind` only if the
+/// strategy stored it.
+fn land<K, V>(inner: &dyn CacheStrategy<K, V>, spillover: Option<&dyn Spill<K, V>>, write_behind: Option<&WriteBehind<K, V>>, key: K, value: V)
+where
+    K: Clone + Send + 'static,
+    V: Send + 'static,
+{
+    let pending = write_behind.map(|write_behind| write_behind.pending(&key, &value));
+    if put_spilling(inner, spillover, key, value).is_ok() {
+        if let Some(pending) = pending {
+            pending.confirm();
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Cost-aware cache in the style of GreedyDual-Size-Frequency (GDSF).
+//!
+//! Every entry carries a priority `L + score(frequency, cost)`, where `L` is
+//! an aging value that rises to the priority of each evicted entry. The entry
+//! with the lowest priority is evicted first, so entries that are expensive to
+//! recompute or often read stay longer, while anything left untouched falls
+//! behind newer priorities as `L` grows.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::strategy::deadline_after;
+use crate::sync::Mutex;
+
+/// Maps an entry's access frequency and cost to its score. Negative and NaN
+/// scores are treated as zero.
+pub type CostScorer = Arc<dyn Fn(usize, f64) -> f64 + Send + Sync>;
+
+struct Entry<V> {
+    value: V,
+    expires_at: DateTime<Utc>,
+    frequency: usize,
+    cost: f64,
+    priority: f64,
+    seq: u64,
+}
+
+struct State<K, V> {
+    map: HashMap<K, Entry<V>>,
+    /// Keys by `(priority bits, seq)`. Priorities are never negative, so
+    /// their bit patterns sort like the values; `seq` breaks ties oldest
+    /// first.
+    queue: BTreeMap<(u64, u64), K>,
+    inflation: f64,
+    next_seq: u64,
+}
+
+pub struct CostAwareCache<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    scorer: CostScorer,
+    state: Mutex<State<K, V>>,
+}
+
+impl<K, V> CostAwareCache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Scores entries GDSF-style as `frequency * cost`.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self::with_scorer(capacity, ttl, |frequency, cost| frequency as f64 * cost)
+    }
+
+    /// Like `new`, with a custom score. Higher scores are kept longer.
+    pub fn with_scorer<F>(capacity: usize, ttl: Duration, scorer: F) -> Self
+    where
+        F: Fn(usize, f64) -> f64 + Send + Sync + 'static,
+    {
+        CostAwareCache {
+            capacity,
+            ttl,
+            scorer: Arc::new(scorer),
+            state: Mutex::new(State {
+                map: HashMap::new(),
+                queue: BTreeMap::new(),
+                inflation: 0.0,
+                next_seq: 0,
+            }),
+        }
+    }
+
+    /// Inserts with a cost of `1.0`.
+    pub fn put(&self, key: K, value: V) -> Option<(K, V)> {
+        self.put_with_cost(key, value, 1.0)
+    }
+
+    /// Inserts `value` with the given recompute `cost` and returns the entry
+    /// evicted to make room, if any. Overwriting a key counts as an access.
+    pub fn put_with_cost(&self, key: K, value: V, cost: f64) -> Option<(K, V)> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let mut state = self.state.lock();
+        let frequency = match state.map.remove(&key) {
+            Some(old) => {
+                state.queue.remove(&(old.priority.to_bits(), old.seq));
+                old.frequency + 1
+            }
+            None => 1,
+        };
+
+        let mut evicted = None;
+        while state.map.len() >= self.capacity {
+            let Some(victim) = Self::evict_one(&mut state) else {
+                break;
+            };
+            evicted.get_or_insert(victim);
+        }
+
+        let cost = if cost.is_finite() { cost.max(0.0) } else { 0.0 };
+        let entry = Entry {
+            value,
+            expires_at: deadline_after(Utc::now(), self.ttl),
+            frequency,
+            cost,
+            priority: 0.0,
+            seq: 0,
+        };
+        self.enqueue(&mut state, key, entry);
+        evicted
+    }
+
+    /// Returns the value and raises the entry's priority. Expired entries
+    /// are dropped.
+    pub fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let mut state = self.state.lock();
+        let mut entry = state.map.remove(key)?;
+        state.queue.remove(&(entry.priority.to_bits(), entry.seq));
+        if entry.expires_at <= Utc::now() {
+            return None;
+        }
+        entry.frequency += 1;
+        let value = entry.value.clone();
+        self.enqueue(&mut state, key.clone(), entry);
+        Some(value)
+    }
+
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let mut state = self.state.lock();
+        let entry = state.map.remove(key)?;
+        state.queue.remove(&(entry.priority.to_bits(), entry.seq));
+        Some(entry.value)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        let state = self.state.lock();
+        state.map.get(key).is_some_and(|entry| entry.expires_at > Utc::now())
+    }
+
+    /// The key the next eviction would remove, without evicting it.
+    pub fn peek_victim(&self) -> Option<K> {
+        let state = self.state.lock();
+        state.queue.values().next().cloned()
+    }
+
+    /// The entry's current priority, for tuning a scorer.
+    pub fn priority(&self, key: &K) -> Option<f64> {
+        let state = self.state.lock();
+        state.map.get(key).map(|entry| entry.priority)
+    }
+
+    /// Drops every expired entry and returns how many there were. Expired
+    /// entries are otherwise only dropped when read or evicted.
+    pub fn purge_expired(&self) -> usize {
+        let mut state = self.state.lock();
+        let now = Utc::now();
+        let expired: Vec<K> = state.map.iter()
+            .filter(|(_, entry)| entry.expires_at <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            if let Some(entry) = state.map.remove(key) {
+                state.queue.remove(&(entry.priority.to_bits(), entry.seq));
+            }
+        }
+        expired.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn enqueue(&self, state: &mut State<K, V>, key: K, mut entry: Entry<V>) {
+        let score = (self.scorer)(entry.frequency, entry.cost);
+        let score = if score.is_nan() { 0.0 } else { score.max(0.0) };
+        entry.priority = state.inflation + score;
+        entry.seq = state.next_seq;
+        state.next_seq += 1;
+        state.queue.insert((entry.priority.to_bits(), entry.seq), key.clone());
+        state.map.insert(key, entry);
+    }
+
+    fn evict_one(state: &mut State<K, V>) -> Option<(K, V)> {
+        let (_, key) = state.queue.pop_first()?;
+        let entry = state.map.remove(&key)?;
+        state.inflation = entry.priority;
+        Some((key, entry.value))
+    }
+}
@@ -0,0 +1,97 @@
+use std::hash::Hash;
+
+use crate::rustycache::Rustycache;
+
+/// Where `LayeredCache::put` writes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WritePolicy {
+    /// Write to both L1 and L2.
+    #[default]
+    WriteThrough,
+    /// Write to L2 only, dropping any stale L1 copy; L1 is filled by
+    /// promotion on reads.
+    L2Only,
+}
+
+/// Two-tier cache: a small, fast L1 in front of a larger L2.
+///
+/// Reads check L1 first and fall back to L2, promoting L2 hits into L1.
+pub struct LayeredCache<K, V> {
+    l1: Rustycache<K, V>,
+    l2: Rustycache<K, V>,
+    write_policy: WritePolicy,
+    demote_on_evict: bool,
+}
+
+impl<K, V> LayeredCache<K, V>
+where
+    K: 'static + Send + Sync + Clone + Eq + Hash,
+    V: 'static + Send + Sync + Clone,
+{
+    pub fn new(l1: Rustycache<K, V>, l2: Rustycache<K, V>) -> Self {
+        LayeredCache {
+            l1,
+            l2,
+            write_policy: WritePolicy::default(),
+            demote_on_evict: false,
+        }
+    }
+
+    pub fn with_write_policy(mut self, write_policy: WritePolicy) -> Self {
+        self.write_policy = write_policy;
+        self
+    }
+
+    /// When enabled, entries evicted from L1 to make room are written to L2.
+    pub fn with_demotion(mut self, demote_on_evict: bool) -> Self {
+        self.demote_on_evict = demote_on_evict;
+        self
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        if let Some(value) = self.l1.get(key) {
+            return Some(value);
+        }
+        let value = self.l2.get(key)?;
+        self.put_l1(key.clone(), value.clone());
+        Some(value)
+    }
+
+    pub fn put(&mut self, key: K, value: V) {
+        match self.write_policy {
+            WritePolicy::WriteThrough => self.put_l1(key.clone(), value.clone()),
+            WritePolicy::L2Only => {
+                self.l1.remove(&key);
+            }
+        }
+        self.l2.insert(key, value);
+    }
+
+    pub fn remove(&mut self, key: &K) {
+        self.l1.remove(key);
+        self.l2.remove(key);
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.l1.contains(key) || self.l2.contains(key)
+    }
+
+    pub fn l1(&self) -> &Rustycache<K, V> {
+        &self.l1
+    }
+
+    pub fn l2(&self) -> &Rustycache<K, V> {
+        &self.l2
+    }
+
+    fn put_l1(&mut self, key: K, value: V) {
+        if !self.demote_on_evict {
+            self.l1.insert(key, value);
+            return;
+        }
+
+        for (evicted_key, evicted_value) in self.l1.put_evicting_all(key, value) {
+            self.l2.insert(evicted_key, evicted_value);
+        }
+    }
+}
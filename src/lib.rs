@@ -1,3 +1,21 @@
-
+mod coalesce;
+pub mod cost_aware;
+pub mod layered;
+mod macros;
+pub mod registry;
+mod reservation;
 pub mod rustycache;
+#[cfg(feature = "server")]
+pub mod server;
+mod spillover;
 pub mod strategy;
+mod sync;
+mod write_behind;
+
+pub use crate::rustycache::{Rustycache, SharedRustycache};
+#[doc(hidden)]
+pub use crate::macros::parse_duration as __parse_duration;
+
+/// Former name of [`Rustycache`], kept so existing code keeps compiling.
+#[deprecated(note = "use `Rustycache` instead")]
+pub type Easycache<K, V> = Rustycache<K, V>;
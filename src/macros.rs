@@ -0,0 +1,88 @@
+//! The `cache!` macro.
+
+use std::time::Duration;
+
+/// Builds a populated `Rustycache` inline.
+///
+/// ```
+/// use rustycache::cache;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut cache = cache!(lru, cap = 100, ttl = 5s, { "a" => 1, "b" => 2 });
+/// assert_eq!(cache.get(&"a"), Some(1));
+/// # }
+/// ```
+///
+/// The strategy is one of `lru`, `fifo` or `lfu`. Options are optional and
+/// map onto `RustycacheBuilder`: `cap`, `ttl` and `interval` (the cleaner
+/// interval). Durations are an integer with an `ms`, `s`, `m` or `h` unit.
+/// The trailing `{ key => value, ... }` body may be empty or left out.
+/// Panics on options `RustycacheBuilder::try_build` would reject, such as
+/// `interval = 0ms`.
+#[macro_export]
+macro_rules! cache {
+    (@strategy lru) => { $crate::strategy::StrategyType::LRU };
+    (@strategy fifo) => { $crate::strategy::StrategyType::FIFO };
+    (@strategy lfu) => { $crate::strategy::StrategyType::LFU };
+    (@duration $duration:literal) => {{
+        const DURATION: ::std::time::Duration = $crate::__parse_duration(stringify!($duration));
+        DURATION
+    }};
+
+    (@build $builder:expr; $(,)?) => { $builder.build() };
+    (@build $builder:expr; { $($key:expr => $value:expr),* $(,)? } $(,)?) => {{
+        let mut cache = $builder.build();
+        $( cache.insert($key, $value); )*
+        cache
+    }};
+    (@build $builder:expr; cap = $cap:expr $(, $($rest:tt)*)?) => {
+        $crate::cache!(@build $builder.capacity($cap); $($($rest)*)?)
+    };
+    (@build $builder:expr; ttl = $ttl:literal $(, $($rest:tt)*)?) => {
+        $crate::cache!(@build $builder.ttl($crate::cache!(@duration $ttl)); $($($rest)*)?)
+    };
+    (@build $builder:expr; interval = $interval:literal $(, $($rest:tt)*)?) => {
+        $crate::cache!(@build $builder.clean_interval($crate::cache!(@duration $interval)); $($($rest)*)?)
+    };
+
+    ($strategy:ident $(, $($rest:tt)*)?) => {
+        $crate::cache!(
+            @build $crate::rustycache::RustycacheBuilder::new($crate::cache!(@strategy $strategy));
+            $($($rest)*)?
+        )
+    };
+}
+
+/// Parses `cache!` durations such as `250ms` or `5s`. Being `const`, a bad
+/// duration fails the build rather than panicking at runtime.
+#[doc(hidden)]
+pub const fn parse_duration(text: &str) -> Duration {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    let mut amount: u64 = 0;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        amount = match amount.checked_mul(10) {
+            Some(n) => match n.checked_add((bytes[i] - b'0') as u64) {
+                Some(n) => n,
+                None => panic!("cache! duration overflows"),
+            },
+            None => panic!("cache! duration overflows"),
+        };
+        i += 1;
+    }
+    if i == 0 {
+        panic!("cache! duration must start with a number");
+    }
+    let unit_secs = match bytes.len() - i {
+        1 if bytes[i] == b's' => 1,
+        1 if bytes[i] == b'm' => 60,
+        1 if bytes[i] == b'h' => 3600,
+        2 if bytes[i] == b'm' && bytes[i + 1] == b's' => return Duration::from_millis(amount),
+        _ => panic!("cache! duration unit must be ms, s, m or h"),
+    };
+    match amount.checked_mul(unit_secs) {
+        Some(secs) => Duration::from_secs(secs),
+        None => panic!("cache! duration overflows"),
+    }
+}
@@ -0,0 +1,69 @@
+use std::hash::Hash;
+
+use tokio::task::{AbortHandle, JoinSet};
+
+use crate::rustycache::Rustycache;
+
+trait Managed: Send + Sync {
+    fn stop(&self);
+}
+
+impl<K, V> Managed for Rustycache<K, V>
+where
+    K: 'static + Send + Sync + Clone + Eq + Hash,
+    V: 'static + Send + Sync,
+{
+    fn stop(&self) {
+        self.stop_cleaner();
+    }
+}
+
+/// Owns a set of caches, of any key and value types, and their background
+/// cleaners so they can be shut down together.
+#[derive(Default)]
+pub struct CacheRegistry {
+    caches: Vec<Box<dyn Managed>>,
+    cleaners: JoinSet<()>,
+    aborts: Vec<AbortHandle>,
+}
+
+impl CacheRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes over the cleaner tasks of `cache`. The registry keeps its own
+    /// handle to the cache, so the storage lives at least until shutdown.
+    pub fn register<K, V>(&mut self, cache: &Rustycache<K, V>)
+    where
+        K: 'static + Send + Sync + Clone + Eq + Hash,
+        V: 'static + Send + Sync,
+    {
+        for handle in cache.take_cleaners() {
+            self.aborts.push(handle.abort_handle());
+            self.cleaners.spawn(async move {
+                let _ = handle.await;
+            });
+        }
+        self.caches.push(Box::new(cache.clone()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.caches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.caches.is_empty()
+    }
+
+    /// Stops every registered cleaner and waits for all of them to exit.
+    pub async fn shutdown_all(&mut self) {
+        for cache in self.caches.drain(..) {
+            cache.stop();
+        }
+        for abort in self.aborts.drain(..) {
+            abort.abort();
+        }
+        while self.cleaners.join_next().await.is_some() {}
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+/// Keys whose value is currently being computed on a miss. The first caller
+/// to miss on a key takes the reservation; later callers wait for it to be
+/// released instead of computing the same value again.
+pub(crate) struct Reservations<K> {
+    pending: Mutex<HashMap<K, Arc<Slot>>>,
+}
+
+#[derive(Default)]
+struct Slot {
+    released: Mutex<bool>,
+    ready: Condvar,
+    ready_async: Notify,
+}
+
+pub(crate) enum Reservation<'a, K: Eq + Hash> {
+    Owner(ReservationGuard<'a, K>),
+    Waiter(Waiter),
+}
+
+/// Held by the computing caller. Dropping it, including on panic, releases
+/// the key and wakes every waiter.
+pub(crate) struct ReservationGuard<'a, K: Eq + Hash> {
+    reservations: &'a Reservations<K>,
+    key: K,
+    slot: Arc<Slot>,
+}
+
+pub(crate) struct Waiter {
+    slot: Arc<Slot>,
+}
+
+impl<K: Eq + Hash + Clone> Reservations<K> {
+    pub(crate) fn new() -> Self {
+        Reservations { pending: Mutex::new(HashMap::new()) }
+    }
+
+    pub(crate) fn reserve(&self, key: &K) -> Reservation<'_, K> {
+        let mut pending = self.pending.lock().unwrap();
+        match pending.get(key) {
+            Some(slot) => Reservation::Waiter(Waiter { slot: Arc::clone(slot) }),
+            None => {
+                let slot = Arc::new(Slot::default());
+                pending.insert(key.clone(), Arc::clone(&slot));
+                Reservation::Owner(ReservationGuard { reservations: self, key: key.clone(), slot })
+            }
+        }
+    }
+
+    /// A waiter on `key`'s in-flight computation, without reserving it if
+    /// there is none.
+    pub(crate) fn pending(&self, key: &K) -> Option<Waiter> {
+        let pending = self.pending.lock().unwrap();
+        pending.get(key).map(|slot| Waiter { slot: Arc::clone(slot) })
+    }
+}
+
+impl<K: Eq + Hash> Drop for ReservationGuard<'_, K> {
+    fn drop(&mut self) {
+        if let Ok(mut pending) = self.reservations.pending.lock() {
+            pending.remove(&self.key);
+        }
+        if let Ok(mut released) = self.slot.released.lock() {
+            *released = true;
+        }
+        self.slot.ready.notify_all();
+        self.slot.ready_async.notify_waiters();
+    }
+}
+
+impl Waiter {
+    /// Blocks the thread until the owner has stored its value or given up.
+    /// Sync callers only; from async code use `wait_timeout`.
+    pub(crate) fn wait(self) {
+        let mut released = self.slot.released.lock().unwrap();
+        while !*released {
+            released = self.slot.ready.wait(released).unwrap();
+        }
+    }
+
+    /// Waits up to `timeout` for the owner to release the key, without
+    /// blocking the runtime. Returns whether it was released in time.
+    pub(crate) async fn wait_timeout(self, timeout: Duration) -> bool {
+        let notified = self.slot.ready_async.notified();
+        tokio::pin!(notified);
+        // Registered before checking the flag, so a release in between is
+        // not missed.
+        notified.as_mut().enable();
+        if *self.slot.released.lock().unwrap() {
+            return true;
+        }
+        tokio::time::timeout(timeout, notified).await.is_ok()
+    }
+}
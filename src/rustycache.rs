@@ -1,50 +1,1122 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
 use std::time::Duration;
-use crate::strategy::{CacheStrategy, StrategyType};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use chrono::{DateTime, Utc};
+
+use crate::coalesce::PutCoalescer;
+use crate::reservation::{Reservation, Reservations};
+use crate::spillover::{put_spilling, settle_spilled, Spill};
+use crate::strategy::{CacheEvent, Clock, CacheItem, CacheOptions, CacheStats, CacheStrategy, GetResult, EvictReason, EvictionTiming, ExpirationPolicy, FifoReplaceMode, PromotePolicy, StrategyType};
 use crate::strategy::fifo::FIFOCache;
 use crate::strategy::lfu::LFUCache;
 use crate::strategy::lru::LRUCache;
+use crate::write_behind::{PendingWrite, WriteBehind};
+
+/// Configures a `Rustycache` beyond the positional arguments of `new`.
+///
+/// Defaults: capacity 100, TTL 60s, cleaner every 10s, eager eviction.
+#[derive(Clone)]
+pub struct RustycacheBuilder {
+    strategy: StrategyType,
+    capacity: usize,
+    ttl: Duration,
+    clean_interval: Option<Duration>,
+    options: CacheOptions,
+}
+
+impl RustycacheBuilder {
+    pub fn new(strategy: StrategyType) -> Self {
+        RustycacheBuilder {
+            strategy,
+            capacity: 100,
+            ttl: Duration::from_secs(60),
+            clean_interval: Some(Duration::from_secs(10)),
+            options: CacheOptions::default(),
+        }
+    }
+
+    /// Maximum number of entries. A capacity of 0 yields a cache that
+    /// silently drops every insert.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn clean_interval(mut self, clean_interval: Duration) -> Self {
+        self.clean_interval = Some(clean_interval);
+        self
+    }
+
+    /// Spawns no cleaner task. Expired entries are dropped on access, or by
+    /// calling `evict_expired` from a scheduler of your own.
+    pub fn without_cleaner(mut self) -> Self {
+        self.clean_interval = None;
+        self
+    }
+
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.options.deterministic = deterministic;
+        self
+    }
+
+    pub fn eviction_timing(mut self, eviction_timing: EvictionTiming) -> Self {
+        self.options.eviction_timing = eviction_timing;
+        self
+    }
+
+    /// Evicts in batches: a put into a cache holding `high` entries first
+    /// evicts down to `low`. Replaces the capacity bound and any
+    /// `eviction_timing` setting.
+    pub fn watermarks(mut self, low: usize, high: usize) -> Self {
+        self.options.eviction_timing = EvictionTiming::Watermarks { low: low.min(high.saturating_sub(1)), high };
+        self
+    }
+
+    /// Sets the expiration policy the cache starts with. Defaults to
+    /// `ExpirationPolicy::Absolute` for every strategy.
+    pub fn expiration_policy(mut self, policy: ExpirationPolicy) -> Self {
+        self.options.expiration_policy = policy;
+        self
+    }
+
+    /// Bounds how many expired entries each cleaner pass removes while
+    /// holding the lock, trading sweep duration for tail latency.
+    pub fn cleaner_batch(mut self, batch: usize) -> Self {
+        self.options.cleaner_batch = Some(batch);
+        self
+    }
+
+    /// Caps the time each cleaner tick spends removing expired entries, in
+    /// ascending expiry order; whatever is left waits for the next tick.
+    /// Combine with `cleaner_batch` to also bound each lock hold. Time is
+    /// read from the cache's clock, so under a `MockClock` that is not
+    /// advanced mid-tick, a zero budget allows exactly one batch per tick.
+    pub fn cleaner_time_budget(mut self, budget: Duration) -> Self {
+        self.options.cleaner_time_budget = Some(budget);
+        self
+    }
+
+    /// Sets how FIFO caches handle a put for an existing key. Ignored by
+    /// other strategies.
+    pub fn fifo_replace_mode(mut self, mode: FifoReplaceMode) -> Self {
+        self.options.fifo_replace = Some(mode);
+        self
+    }
+
+    /// Makes every put reclaim up to `per_put` expired entries first. Useful
+    /// together with `stop_cleaner` when no background task is wanted.
+    pub fn amortized_cleanup(mut self, per_put: usize) -> Self {
+        self.options.amortized_cleanup = Some(per_put);
+        self
+    }
+
+    /// LRU only: promotes a key on every `n`th access rather than every
+    /// access, reducing reordering under read-heavy load.
+    pub fn promote_every(mut self, n: usize) -> Self {
+        self.options.promote_every = Some(n);
+        self
+    }
+
+    /// Reads time from `clock` instead of the wall clock. With a
+    /// `MockClock` and `without_cleaner`, entries expire only when the
+    /// simulation advances the clock, and `step_cleaner` sweeps them.
+    pub fn clock(mut self, clock: impl Into<Clock>) -> Self {
+        self.options.clock = clock.into();
+        self
+    }
 
+    fn validate(&self) -> Result<(), CacheConfigError> {
+        if self.clean_interval.is_some_and(|interval| interval.is_zero()) {
+            return Err(CacheConfigError::ZeroCleanInterval);
+        }
+        let representable = chrono::Duration::from_std(self.ttl)
+            .ok()
+            .and_then(|ttl| Utc::now().checked_add_signed(ttl))
+            .is_some();
+        if !representable {
+            return Err(CacheConfigError::TtlOutOfRange(self.ttl));
+        }
+        Ok(())
+    }
+
+    /// Panics on a configuration `try_build` would reject.
+    pub fn build<K, V>(self) -> Rustycache<K, V>
+    where
+        K: 'static + Send + Sync + Clone + Eq + Hash,
+        V: 'static + Send + Sync,
+    {
+        self.try_build().unwrap_or_else(|err| panic!("invalid cache config: {err}"))
+    }
+
+    /// Like `build`, but reports a zero clean interval or a TTL too large to
+    /// turn into a deadline instead of misbehaving later.
+    pub fn try_build<K, V>(self) -> Result<Rustycache<K, V>, CacheConfigError>
+    where
+        K: 'static + Send + Sync + Clone + Eq + Hash,
+        V: 'static + Send + Sync,
+    {
+        self.validate()?;
+        Ok(self.build_unchecked())
+    }
+
+    fn build_unchecked<K, V>(self) -> Rustycache<K, V>
+    where
+        K: 'static + Send + Sync + Clone + Eq + Hash,
+        V: 'static + Send + Sync,
+    {
+        let (cap, ttl, options) = (self.capacity, self.ttl, self.options.clone());
+        let inner: Arc<dyn CacheStrategy<K, V>> = match self.strategy {
+            StrategyType::LRU => Arc::new(LRUCache::without_cleaner(cap, ttl, options)),
+            StrategyType::FIFO => Arc::new(FIFOCache::without_cleaner(cap, ttl, options)),
+            StrategyType::LFU => Arc::new(LFUCache::without_cleaner(cap, ttl, options)),
+        };
+
+        if let Some(clean_interval) = self.clean_interval {
+            inner.start_cleaner(clean_interval);
+        }
+
+        Rustycache {
+            inner,
+            reservations: Arc::new(Reservations::new()),
+            write_behind: None,
+            coalescer: None,
+            spillover: None,
+            loader: None,
+            key_normalizer: None,
+            value_limit: None,
+            config: self,
+        }
+    }
+}
+
+/// Why `Rustycache::try_new` or `RustycacheBuilder::try_build` rejected its arguments. Capacity is not
+/// checked: 0 is a valid, if useless, cache that stores nothing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CacheConfigError {
+    /// The cleaner would run in a busy loop.
+    ZeroCleanInterval,
+    /// Deadlines computed from this TTL would not fit in a `DateTime<Utc>`.
+    TtlOutOfRange(Duration),
+}
+
+impl fmt::Display for CacheConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheConfigError::ZeroCleanInterval => write!(f, "clean interval must be non-zero"),
+            CacheConfigError::TtlOutOfRange(ttl) => write!(f, "ttl {:?} is too large to compute deadlines", ttl),
+        }
+    }
+}
+
+impl std::error::Error for CacheConfigError {}
+
+type Loader<K, V> = Arc<dyn Fn(&K) -> Option<V> + Send + Sync>;
+type KeyNormalizer<K> = Arc<dyn Fn(&K) -> K + Send + Sync>;
+type Weigher<V> = Arc<dyn Fn(&V) -> usize + Send + Sync>;
+
+/// What `hydrate_from_with` does when a key exists in both caches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HydrateConflict {
+    /// Keep whichever entry has more time left to live.
+    #[default]
+    KeepFresher,
+    /// Always keep the entry already in this cache.
+    KeepExisting,
+    /// Always take the entry from the other cache.
+    Overwrite,
+}
+
+/// Everything `report` gathers about a cache, meant for a periodic one-line
+/// log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CacheReport {
+    pub strategy_name: &'static str,
+    pub capacity: usize,
+    pub ttl: Duration,
+    /// Stored entries, including expired ones not yet cleaned up.
+    pub len: usize,
+    /// Stored entries that have not expired.
+    pub live_len: usize,
+    pub stats: CacheStats,
+}
+
+/// A cache handle. Clones are cheap and share the same underlying storage,
+/// so a cache can be handed to several tasks.
 pub struct Rustycache<K, V> {
-    inner: Box<dyn CacheStrategy<K, V>>,
+    inner: Arc<dyn CacheStrategy<K, V>>,
+    reservations: Arc<Reservations<K>>,
+    write_behind: Option<Arc<WriteBehind<K, V>>>,
+    coalescer: Option<Arc<PutCoalescer<K, V>>>,
+    spillover: Option<Arc<dyn Spill<K, V>>>,
+    loader: Option<Loader<K, V>>,
+    key_normalizer: Option<KeyNormalizer<K>>,
+    value_limit: Option<(usize, Weigher<V>)>,
+    config: RustycacheBuilder,
+}
+
+/// A cache in shared value mode: values are stored behind an `Arc`, so
+/// reads hand out a pointer clone instead of deep-copying a large value.
+pub type SharedRustycache<K, V> = Rustycache<K, Arc<V>>;
+
+impl<K, V> Clone for Rustycache<K, V> {
+    fn clone(&self) -> Self {
+        Rustycache {
+            inner: Arc::clone(&self.inner),
+            reservations: Arc::clone(&self.reservations),
+            write_behind: self.write_behind.clone(),
+            coalescer: self.coalescer.clone(),
+            spillover: self.spillover.clone(),
+            loader: self.loader.clone(),
+            key_normalizer: self.key_normalizer.clone(),
+            value_limit: self.value_limit.clone(),
+            config: self.config.clone(),
+        }
+    }
 }
 
 impl<K, V> Rustycache<K, V>
 where
-    K: 'static + Send + Sync + Clone + Eq + std::hash::Hash,
-    V: 'static + Send + Sync + Clone,
+    K: 'static + Send + Sync + Clone + Eq + Hash,
+    V: 'static + Send + Sync,
 {
+    /// Panics on arguments `try_new` would reject.
     pub fn new(cap: usize, ttl: Duration, clean_interval: Duration, strat: StrategyType) -> Self {
-        let inner: Box<dyn CacheStrategy<K, V>> = match strat {
-            StrategyType::LRU => Box::new(LRUCache::new(cap, ttl, clean_interval)),
-            StrategyType::FIFO => Box::new(FIFOCache::new(cap, ttl, clean_interval)),
-            StrategyType::LFU => Box::new(LFUCache::new(cap, ttl, clean_interval)),
-        };
+        Self::try_new(cap, ttl, clean_interval, strat).unwrap_or_else(|err| panic!("invalid cache config: {err}"))
+    }
+
+    /// Like `new`, but reports a zero clean interval or a TTL too large to
+    /// turn into a deadline instead of misbehaving later.
+    pub fn try_new(cap: usize, ttl: Duration, clean_interval: Duration, strat: StrategyType) -> Result<Self, CacheConfigError> {
+        let builder = RustycacheBuilder::new(strat)
+            .capacity(cap)
+            .ttl(ttl)
+            .clean_interval(clean_interval);
+        builder.try_build()
+    }
+
+    /// Like `new`, but adopts the entries of `map`, moving them in under a
+    /// single lock with the default TTL. If `map` holds more than `cap`
+    /// entries the surplus is evicted as inserts would evict it; since a
+    /// `HashMap` has no order, which ones survive is unspecified.
+    pub fn from_map(map: HashMap<K, V>, cap: usize, ttl: Duration, clean_interval: Duration, strat: StrategyType) -> Self {
+        let cache = Self::new(cap, ttl, clean_interval, strat);
+        cache.inner.put_many(&mut map.into_iter());
+        cache
+    }
 
-        inner.start_cleaner(clean_interval);
+    /// Like `new`, but starts from entries taken out of another cache with
+    /// `into_entries`, each keeping its deadline. Entries are inserted in
+    /// the given order, so rebuilding with the same strategy reproduces the
+    /// eviction order; under LFU every entry starts at frequency 1. Expired
+    /// entries are skipped.
+    pub fn from_entries(
+        entries: Vec<(K, V, DateTime<Utc>)>,
+        cap: usize,
+        ttl: Duration,
+        clean_interval: Duration,
+        strat: StrategyType,
+    ) -> Self {
+        let cache = Self::new(cap, ttl, clean_interval, strat);
+        cache.inner.restore(&mut entries.into_iter());
+        cache
+    }
+
+    /// Like `new`, but every `get` is read-through: on a miss `loader` is
+    /// called and a `Some` result is cached before being returned.
+    pub fn with_loader<F>(cap: usize, ttl: Duration, clean_interval: Duration, strat: StrategyType, loader: F) -> Self
+    where
+        F: Fn(&K) -> Option<V> + Send + Sync + 'static,
+    {
+        let mut cache = Self::new(cap, ttl, clean_interval, strat);
+        cache.loader = Some(Arc::new(loader));
+        cache
+    }
+
+    /// Like `new`, but with a fixed internal hasher so that eviction order is
+    /// reproducible for identical operation sequences.
+    pub fn new_deterministic(cap: usize, ttl: Duration, clean_interval: Duration, strat: StrategyType) -> Self {
+        RustycacheBuilder::new(strat)
+            .capacity(cap)
+            .ttl(ttl)
+            .clean_interval(clean_interval)
+            .deterministic(true)
+            .build()
+    }
+
+    /// Forwards every write made through `insert`, `put_evicting`,
+    /// `put_permanent`, `put_with_ttl` and `get_or_insert_with_ttl` to `sink`
+    /// from a background task, so the backing store is updated off the hot
+    /// path. A write the cache drops, such as a zero-capacity put or a FIFO
+    /// put to a key it keeps, is not forwarded. Pending writes are handed
+    /// over once `batch_size` accumulate, on every `flush_interval`, or on
+    /// `flush`. A zero `flush_interval` is treated as one millisecond.
+    pub fn with_write_behind<F, Fut>(mut self, sink: F, batch_size: usize, flush_interval: Duration) -> Self
+    where
+        F: Fn(K, V) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        V: Clone,
+    {
+        self.write_behind = Some(Arc::new(WriteBehind::spawn(sink, batch_size, flush_interval)));
+        self
+    }
+
+    /// Writes entries that puts push out for capacity to `dir`, one JSON
+    /// file each, keeping at most `disk_capacity` of them. Any later write
+    /// to a key drops its spilled copy. A `get` that
+    /// misses in memory but hits on disk moves the entry back in, with a
+    /// fresh TTL. A spilled entry is gone once it has been on disk longer
+    /// than the TTL. Fails only if `dir` can't be created.
+    #[cfg(feature = "spillover")]
+    pub fn with_spillover(mut self, dir: impl Into<std::path::PathBuf>, disk_capacity: usize) -> std::io::Result<Self>
+    where
+        V: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let store = crate::spillover::DiskSpillover::open(dir.into(), disk_capacity, self.config.options.clock.clone())?;
+        self.spillover = Some(Arc::new(store));
+        Ok(self)
+    }
+
+    /// Buffers `insert`s for `window` so a burst of writes to one key costs
+    /// a single locked put of the last value. Until the window closes, reads
+    /// may still see the value from before the burst, and a buffered value
+    /// replaces anything written to the key through other methods meanwhile.
+    /// `remove`, `take` and the `clear` family drop buffered values.
+    pub fn with_put_coalescing(mut self, window: Duration) -> Self {
+        self.coalescer = Some(Arc::new(PutCoalescer::new(window)));
+        self
+    }
+
+    /// Applies any puts buffered by `with_put_coalescing`, then waits until
+    /// every write queued so far has been passed to the write-behind sink.
+    pub async fn flush(&self) {
+        if let Some(coalescer) = &self.coalescer {
+            coalescer.apply_all(&*self.inner, self.spillover.as_deref(), self.write_behind.as_deref());
+        }
+        if let Some(write_behind) = &self.write_behind {
+            write_behind.flush().await;
+        }
+    }
+
+    /// Maps every key passed to this handle through `normalizer` before it
+    /// reaches the cache, e.g. to lowercase or trim, so call sites don't have
+    /// to. It must be consistent: normalizing an already normalized key has
+    /// to return it unchanged. Entries stored earlier are not rewritten, so
+    /// set it before inserting.
+    pub fn with_key_normalizer<F>(mut self, normalizer: F) -> Self
+    where
+        F: Fn(&K) -> K + Send + Sync + 'static,
+    {
+        self.key_normalizer = Some(Arc::new(normalizer));
+        self
+    }
+
+    /// Refuses to cache any value whose `weigher` result exceeds
+    /// `max_weight`, so a stray huge blob can't crowd out everything else.
+    /// A refused write leaves whatever was stored under the key untouched
+    /// and is not forwarded to a write-behind sink; `try_insert` reports it.
+    /// This bounds single values and is independent of `capacity`.
+    pub fn with_max_value_weight<F>(mut self, max_weight: usize, weigher: F) -> Self
+    where
+        F: Fn(&V) -> usize + Send + Sync + 'static,
+    {
+        self.value_limit = Some((max_weight, Arc::new(weigher)));
+        self
+    }
+
+    fn admits(&self, value: &V) -> bool {
+        match &self.value_limit {
+            Some((max_weight, weigher)) => weigher(value) <= *max_weight,
+            None => true,
+        }
+    }
+
+    fn normalize(&self, key: K) -> K {
+        match &self.key_normalizer {
+            Some(normalizer) => normalizer(&key),
+            None => key,
+        }
+    }
+
+    fn normalized<'a>(&self, key: &'a K) -> Cow<'a, K> {
+        match &self.key_normalizer {
+            Some(normalizer) => Cow::Owned(normalizer(key)),
+            None => Cow::Borrowed(key),
+        }
+    }
+
+    fn normalized_all<'a>(&self, keys: &'a [K]) -> Cow<'a, [K]> {
+        match &self.key_normalizer {
+            Some(normalizer) => Cow::Owned(keys.iter().map(|key| normalizer(key)).collect()),
+            None => Cow::Borrowed(keys),
+        }
+    }
+
+    fn discard_coalesced(&self) {
+        if let Some(coalescer) = &self.coalescer {
+            coalescer.discard_all();
+        }
+    }
+
+    fn discard_spilled(&self) {
+        if let Some(spillover) = &self.spillover {
+            spillover.discard_all();
+        }
+    }
+
+    /// Forwards a value that is already stored in the cache to write-behind.
+    fn write_behind(&self, key: &K, value: &V) {
+        if let Some(write_behind) = self.pending_write(key, value) {
+            write_behind.confirm();
+        }
+    }
+
+    /// Copies a write for write-behind before the value moves into the
+    /// strategy. Confirm it only once the strategy reports it stored.
+    fn pending_write(&self, key: &K, value: &V) -> Option<PendingWrite<'_, K, V>> {
+        self.write_behind.as_ref().map(|write_behind| write_behind.pending(key, value))
+    }
 
-        Rustycache { inner }
+    /// Inserts `value` under `key`, evicting according to the strategy if the
+    /// cache is full. A value over the `with_max_value_weight` limit is not
+    /// stored. With `with_put_coalescing`, the write lands when its window
+    /// closes.
+    pub fn insert(&mut self, key: K, value: V) {
+        let _ = self.try_insert(key, value);
     }
 
+    /// Like `insert`, but hands the pair back instead of silently dropping
+    /// it when nothing was stored: the value is over the
+    /// `with_max_value_weight` limit, the cache has zero capacity, or a FIFO
+    /// cache without `fifo_replace_mode` already holds the key. With
+    /// `with_put_coalescing` the put is only buffered, so `Ok` means it was
+    /// accepted into the window.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        if !self.admits(&value) {
+            return Err((key, value));
+        }
+        let key = self.normalize(key);
+        match &self.coalescer {
+            Some(coalescer) => {
+                coalescer.stash(key, value, &self.inner, self.spillover.as_ref(), self.write_behind.as_ref());
+                Ok(())
+            }
+            None => {
+                let pending = self.pending_write(&key, &value);
+                self.put_spilling(key, value)?;
+                if let Some(pending) = pending {
+                    pending.confirm();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Puts straight into the strategy, moving any capacity victim to the
+    /// spillover tier. Unlike `insert`, nothing is forwarded to write-behind.
+    fn put_spilling(&self, key: K, value: V) -> Result<(), (K, V)> {
+        put_spilling(&*self.inner, self.spillover.as_deref(), key, value)
+    }
+
+    /// Keeps the spillover tier in step after a write stored `key`: drops
+    /// its spilled copy and spills what the write `evicted`.
+    fn settle_spilled(&self, key: &K, evicted: Vec<(K, V)>) {
+        settle_spilled(self.spillover.as_deref(), key, evicted);
+    }
+
+    /// Caches a value that came from the loader or the spillover tier. It
+    /// already lives in the backing store, so it skips write-behind.
+    fn fill(&self, key: K, value: V) {
+        if self.admits(&value) {
+            let _ = self.put_spilling(key, value);
+        }
+    }
+
+    #[deprecated(note = "use `insert` instead")]
     pub fn put(&mut self, key: K, value: V) {
-        self.inner.put(key, value)
+        self.insert(key, value)
+    }
+
+    /// Inserts like `put` and returns the entry displaced by capacity
+    /// pressure, if any. An overwritten previous value for `key` is not
+    /// reported. When watermarks evict a batch, only the first victim is
+    /// returned; use `put_evicting_all` to get them all. Returned entries
+    /// are handed to the caller instead of the spillover tier.
+    pub fn put_evicting(&mut self, key: K, value: V) -> Option<(K, V)> {
+        self.put_evicting_all(key, value).into_iter().next()
+    }
+
+    /// Like `put_evicting`, but returns every entry evicted by this put.
+    pub fn put_evicting_all(&mut self, key: K, value: V) -> Vec<(K, V)> {
+        if !self.admits(&value) {
+            return Vec::new();
+        }
+        let key = self.normalize(key);
+        let pending = self.pending_write(&key, &value);
+        let Ok(evicted) = self.inner.put_evicting(key.clone(), value) else {
+            return Vec::new();
+        };
+        self.settle_spilled(&key, Vec::new());
+        if let Some(pending) = pending {
+            pending.confirm();
+        }
+        evicted
+    }
+
+    /// Stores an entry that never expires on TTL grounds. It can still be
+    /// evicted by capacity pressure or removed explicitly.
+    pub fn put_permanent(&mut self, key: K, value: V) {
+        if !self.admits(&value) {
+            return;
+        }
+        let key = self.normalize(key);
+        let pending = self.pending_write(&key, &value);
+        let Ok(evicted) = self.inner.put_permanent(key.clone(), value) else {
+            return;
+        };
+        self.settle_spilled(&key, evicted);
+        if let Some(pending) = pending {
+            pending.confirm();
+        }
+    }
+
+    /// Inserts an entry that expires after `ttl` instead of the cache default.
+    pub fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) {
+        if !self.admits(&value) {
+            return;
+        }
+        let key = self.normalize(key);
+        let pending = self.pending_write(&key, &value);
+        let Ok(evicted) = self.inner.put_with_ttl(key.clone(), value, ttl) else {
+            return;
+        };
+        self.settle_spilled(&key, evicted);
+        if let Some(pending) = pending {
+            pending.confirm();
+        }
+    }
+
+    /// Inserts with the given `ttl` only if `key` is absent or its entry has
+    /// expired, checking and inserting under one lock. Returns whether it
+    /// inserted, so of several racing callers exactly one wins. The check
+    /// does not count as an access.
+    pub fn put_if_absent_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> bool {
+        if !self.admits(&value) {
+            return false;
+        }
+        let key = self.normalize(key);
+        let pending = self.pending_write(&key, &value);
+        let Some(evicted) = self.inner.put_if_absent_with_ttl(key.clone(), value, ttl) else {
+            return false;
+        };
+        self.settle_spilled(&key, evicted);
+        if let Some(pending) = pending {
+            pending.confirm();
+        }
+        true
+    }
+
+    /// Inserts without evicting a live entry: while the cache is full, waits
+    /// for a removal, eviction or expiry to free a slot. Overwrites of an
+    /// existing key never wait. A zero-capacity cache stores nothing, so
+    /// the put is dropped at once instead of waiting forever.
+    pub async fn put_await(&self, key: K, value: V) {
+        if self.inner.capacity() == 0 || !self.admits(&value) {
+            return;
+        }
+        let mut pending = (self.normalize(key), value);
+        loop {
+            let mut events = self.inner.subscribe();
+            self.inner.drain_expired();
+            let key = pending.0.clone();
+            match self.inner.try_put(pending.0, pending.1) {
+                Ok(()) => return self.settle_spilled(&key, Vec::new()),
+                Err(rejected) => pending = rejected,
+            }
+            // `clear` emits no events, so re-check periodically as well.
+            tokio::select! {
+                _ = Self::slot_freed(&mut events) => {}
+                _ = sleep(self.config.clean_interval.unwrap_or(Duration::from_secs(1))) => {}
+            }
+        }
+    }
+
+    async fn slot_freed(events: &mut broadcast::Receiver<CacheEvent<K>>) {
+        loop {
+            match events.recv().await {
+                Ok(CacheEvent::Inserted(_) | CacheEvent::Updated(_)) => continue,
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => return,
+                Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+            }
+        }
+    }
+
+    /// Resolves the next time `key` is inserted, updated, evicted, removed
+    /// or expires. The subscription is taken when `changed` is called, so a
+    /// change made before the returned future is first polled still wakes
+    /// it. Waiters share the event channel, so nothing is left to clean up
+    /// once they are dropped; a lagging waiter wakes spuriously rather than
+    /// missing a change.
+    pub fn changed(&self, key: &K) -> impl Future<Output = ()> + Send + 'static {
+        let key = self.normalized(key).into_owned();
+        let mut events = self.inner.subscribe();
+        async move {
+            loop {
+                match events.recv().await {
+                    Ok(
+                        CacheEvent::Inserted(k)
+                        | CacheEvent::Updated(k)
+                        | CacheEvent::Evicted(k)
+                        | CacheEvent::Expired(k)
+                        | CacheEvent::Removed(k),
+                    ) => {
+                        if k == key {
+                            return;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => return,
+                    Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+                }
+            }
+        }
+    }
+
+    /// Returns the cached value, or inserts `default` and returns it. The
+    /// lookup and insert happen under one lock.
+    pub fn get_or_insert(&mut self, key: K, default: V) -> V
+    where
+        V: Clone,
+    {
+        let key = self.normalize(key);
+        if !self.admits(&default) {
+            return self.inner.get(&key).unwrap_or(default);
+        }
+        let (value, evicted) = self.inner.get_or_insert(key.clone(), default);
+        self.settle_spilled(&key, evicted);
+        value
+    }
+
+    /// Returns the cached value, or computes it with `f` and stores it with
+    /// the given `ttl`. `f` runs outside the cache lock; concurrent callers
+    /// missing on the same key wait for that computation instead of running
+    /// their own.
+    ///
+    /// That wait blocks the calling thread, so do not call this from async
+    /// code: it stalls a runtime worker, and on a current-thread runtime it
+    /// deadlocks if the computing caller is a task on the same thread. Async
+    /// callers should wait with `get_or_wait`, which awaits the computation
+    /// with a timeout instead, or load through `load_with`.
+    pub fn get_or_insert_with_ttl<F: FnOnce() -> V>(&mut self, key: K, ttl: Duration, f: F) -> V
+    where
+        V: Clone,
+    {
+        let key = self.normalize(key);
+        let mut f = Some(f);
+        loop {
+            if let Some(value) = self.inner.get(&key) {
+                return value;
+            }
+            match self.reservations.reserve(&key) {
+                Reservation::Owner(_guard) => {
+                    // Another owner may have finished between our miss and the reservation.
+                    if let Some(value) = self.inner.get(&key) {
+                        return value;
+                    }
+                    let value = (f.take().unwrap())();
+                    if !self.admits(&value) {
+                        return value;
+                    }
+                    if let Ok(evicted) = self.inner.put_with_ttl(key.clone(), value.clone(), ttl) {
+                        self.settle_spilled(&key, evicted);
+                        self.write_behind(&key, &value);
+                    }
+                    return value;
+                }
+                Reservation::Waiter(waiter) => waiter.wait(),
+            }
+        }
+    }
+
+    /// Returns the cached value. On a miss while another caller is computing
+    /// `key` through `get_or_insert_with_ttl`, waits up to `timeout` for that
+    /// computation instead of missing straight away. Without one in flight,
+    /// a miss returns `None` immediately.
+    pub async fn get_or_wait(&self, key: &K, timeout: Duration) -> Option<V>
+    where
+        V: Clone,
+    {
+        let key = self.normalized(key);
+        if let Some(value) = self.inner.get(&key) {
+            return Some(value);
+        }
+        let waiter = self.reservations.pending(&key)?;
+        if !waiter.wait_timeout(timeout).await {
+            return None;
+        }
+        self.inner.get(&key)
+    }
+
+    /// Returns the cached value, or awaits `f` for it. On `Ok((value, ttl))`
+    /// the value is cached for the `ttl` the loader chose, e.g. an upstream
+    /// max-age; on `Err` nothing is cached and the error is returned.
+    /// Concurrent misses are not coalesced.
+    pub async fn load_with<F, Fut, E>(&self, key: K, f: F) -> Result<V, E>
+    where
+        F: FnOnce(&K) -> Fut,
+        Fut: Future<Output = Result<(V, Duration), E>>,
+        V: Clone,
+    {
+        let key = self.normalize(key);
+        if let Some(value) = self.inner.get(&key) {
+            return Ok(value);
+        }
+        let (value, ttl) = f(&key).await?;
+        if !self.admits(&value) {
+            return Ok(value);
+        }
+        if let Ok(evicted) = self.inner.put_with_ttl(key.clone(), value.clone(), ttl) {
+            self.settle_spilled(&key, evicted);
+            self.write_behind(&key, &value);
+        }
+        Ok(value)
+    }
+
+    /// Returns the value for `key`. With a loader configured, a miss calls it
+    /// and caches whatever it returns.
+    pub fn get(&mut self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let key = self.normalized(key);
+        if let Some(value) = self.inner.get(&key) {
+            return Some(value);
+        }
+        if let Some(value) = self.spillover.as_ref().and_then(|spillover| spillover.take(&key, self.inner.ttl())) {
+            self.fill(key.into_owned(), value.clone());
+            return Some(value);
+        }
+        let value = (self.loader.as_ref()?)(&key)?;
+        self.fill(key.into_owned(), value.clone());
+        Some(value)
+    }
+
+    /// Like `get`, but hands `f` a reference to the value instead of a
+    /// clone, so it works for values that aren't `Clone`. `f` runs while the
+    /// cache is locked and must not call back into it.
+    pub fn get_with<R>(&mut self, key: &K, f: impl FnOnce(&V) -> R) -> Option<R> {
+        let key = self.normalized(key);
+        let mut f = Some(f);
+        let mut read = None;
+        self.inner.get_with(&key, &mut |value| read = f.take().map(|f| f(value)));
+        if read.is_some() {
+            return read;
+        }
+        let f = f?;
+        let value = match self.spillover.as_ref().and_then(|spillover| spillover.take(&key, self.inner.ttl())) {
+            Some(value) => value,
+            None => (self.loader.as_ref()?)(&key)?,
+        };
+        let read = f(&value);
+        self.fill(key.into_owned(), value);
+        Some(read)
+    }
+
+    /// Like `get`, but panics when the key is missing or expired, in the
+    /// spirit of `HashMap`'s `Index`. `Index` itself can't be implemented
+    /// since values live behind a lock, so this returns a clone.
+    pub fn get_expect(&mut self, key: &K) -> V
+    where
+        V: Clone,
+    {
+        self.get(key).expect("key not present in cache (missing or expired)")
+    }
+
+    /// Like `get`, but tells a key that was never cached apart from one whose
+    /// entry had expired.
+    pub fn get_detailed(&mut self, key: &K) -> GetResult<V>
+    where
+        V: Clone,
+    {
+        self.inner.get_detailed(&self.normalized(key))
+    }
+
+    /// Returns the value only if it was written no longer than `max_age` ago,
+    /// regardless of how much TTL it has left.
+    pub fn get_if_fresh_within(&mut self, key: &K, max_age: Duration) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.inner.get_if_fresh_within(&self.normalized(key), max_age)
+    }
+
+    /// Like `get`, but also returns a clone of the stored key, mirroring
+    /// `HashMap::get_key_value`.
+    pub fn get_key_value(&mut self, key: &K) -> Option<(K, V)>
+    where
+        V: Clone,
+    {
+        self.inner.get_key_value(&self.normalized(key))
+    }
+
+    /// Like `get`, but returns the value together with when it was inserted,
+    /// when it expires and, for LFU, its access count, all read under one
+    /// lock. Handy for deriving HTTP cache headers. Skips the loader.
+    pub fn get_with_metadata(&mut self, key: &K) -> Option<CacheItem<V>>
+    where
+        V: Clone,
+    {
+        self.inner.get_with_metadata(&self.normalized(key))
+    }
+
+    /// Reads all `keys` under a single lock acquisition, so no concurrent
+    /// write can land between the reads. Hits update recency/frequency
+    /// exactly as `get` would.
+    pub fn get_consistent(&mut self, keys: &[K]) -> Vec<Option<V>>
+    where
+        V: Clone,
+    {
+        self.inner.get_consistent(&self.normalized_all(keys))
+    }
+
+    /// Reads `keys` under one lock like `get_consistent`, but `promote`
+    /// decides which hits move up the eviction order: all of them, only the
+    /// last key, or none. Useful when a group read should leave just its
+    /// final key most recently used.
+    pub fn get_group(&mut self, keys: &[K], promote: PromotePolicy) -> Vec<Option<V>>
+    where
+        V: Clone,
+    {
+        self.inner.get_group(&self.normalized_all(keys), promote)
+    }
+
+    /// Removes the entry and returns its value, like `HashMap::remove`.
+    /// Expired entries are removed but yield `None`.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.take(key)
+    }
+
+    /// Removes the entry and returns its value if it was present and unexpired.
+    pub fn take(&mut self, key: &K) -> Option<V> {
+        let key = self.normalized(key);
+        if let Some(coalescer) = &self.coalescer {
+            coalescer.discard(&key);
+        }
+        if let Some(spillover) = &self.spillover {
+            spillover.discard(&key);
+        }
+        self.inner.take(&key)
+    }
+
+    /// Removes every key in `keys` under one lock. Each slot holds the removed
+    /// value, or `None` if the key was absent or already expired.
+    pub fn remove_many(&mut self, keys: &[K]) -> Vec<Option<V>> {
+        let keys = self.normalized_all(keys);
+        if let Some(coalescer) = &self.coalescer {
+            keys.iter().for_each(|key| coalescer.discard(key));
+        }
+        if let Some(spillover) = &self.spillover {
+            keys.iter().for_each(|key| spillover.discard(key));
+        }
+        self.inner.remove_many(&keys)
+    }
+
+    /// Adds `by` to the remaining lifetime of a live entry. Returns `false`
+    /// if the key is absent or already expired.
+    pub fn extend_ttl(&mut self, key: &K, by: Duration) -> bool {
+        self.inner.extend_ttl(&self.normalized(key), by)
+    }
+
+    /// Resets the deadline of every live entry whose key matches `pred` to
+    /// `now + ttl` and returns how many were refreshed. Permanent entries are
+    /// left alone.
+    pub fn touch_matching<F: Fn(&K) -> bool>(&mut self, pred: F) -> usize {
+        self.inner.touch_matching(&pred)
+    }
+
+    /// Heartbeat check: if `key` is live, resets its deadline to `now + ttl`,
+    /// counts it as an access for recency or frequency, and returns `true`.
+    /// Done under one lock without cloning the value, so it is cheaper than
+    /// `get` when only presence matters. Permanent entries stay permanent.
+    pub fn contains_and_refresh(&mut self, key: &K) -> bool {
+        self.inner.contains_and_refresh(&self.normalized(key))
+    }
+
+    /// Applies `f` in place to every live entry among `keys` under a single
+    /// lock, counting each as an access. Absent and expired keys are skipped.
+    /// Each updated key emits `CacheEvent::Updated`, waking `changed`.
+    pub fn update_many<F: FnMut(&K, &mut V)>(&mut self, keys: &[K], mut f: F) {
+        let keys = self.normalized_all(keys);
+        self.inner.update_many(&keys, &mut |key, value| {
+            f(key, value);
+            self.write_behind(key, value);
+        });
+    }
+
+    /// Exempts a live entry from capacity eviction; it still counts toward
+    /// `len` and still expires. When only pinned entries remain, inserts grow
+    /// the cache past its capacity instead of evicting. Returns `false` if the
+    /// key is absent or expired.
+    pub fn pin(&mut self, key: &K) -> bool {
+        self.inner.pin(&self.normalized(key))
+    }
+
+    /// Makes a pinned entry evictable again.
+    pub fn unpin(&mut self, key: &K) {
+        self.inner.unpin(&self.normalized(key))
+    }
+
+    /// Evicts entries in the strategy's eviction order until at most
+    /// `target_len` remain, without changing the configured capacity.
+    /// Returns the number of evicted entries.
+    pub fn prune_to(&mut self, target_len: usize) -> usize {
+        self.inner.prune_to(target_len)
     }
 
-    pub fn get(&mut self, key: &K) -> Option<V> {
-        self.inner.get(key)
+    /// The key the next capacity eviction would remove, without evicting it.
+    pub fn peek_victim(&self) -> Option<K> {
+        self.inner.peek_victim()
     }
 
-    pub fn remove(&mut self, key: &K) {
-        self.inner.remove(key)
+    /// Keys in eviction priority, next victim first: insertion order for
+    /// FIFO, least recently used first for LRU, ascending frequency for LFU.
+    pub fn debug_order(&self) -> Vec<K> {
+        self.inner.debug_order()
     }
 
     pub fn contains(&self, key: &K) -> bool {
-        self.inner.contains(key)
+        self.inner.contains(&self.normalized(key))
+    }
+
+    /// Groups live entries by `key_fn` and counts each group, e.g. entries per
+    /// tenant prefix.
+    pub fn count_by<G, F>(&self, key_fn: F) -> HashMap<G, usize>
+    where
+        G: Eq + Hash,
+        F: Fn(&K) -> G,
+    {
+        let mut counts = HashMap::new();
+        self.inner.for_each_live_key(&mut |key| {
+            *counts.entry(key_fn(key)).or_insert(0) += 1;
+        });
+        counts
+    }
+
+    /// Hit, miss, insertion and eviction counters since the cache was created.
+    pub fn stats(&self) -> CacheStats {
+        self.inner.stats()
+    }
+
+    /// The most recent capacity evictions and expirations, oldest first,
+    /// with when they happened. Only the last `RECENT_EVICTIONS_CAPACITY`
+    /// are kept; explicit removals are not included.
+    pub fn recent_evictions(&self) -> Vec<(K, DateTime<Utc>, EvictReason)> {
+        self.inner.recent_evictions()
+    }
+
+    /// The entries of `recent_evictions` recorded at or after `since`.
+    pub fn evicted_since(&self, since: DateTime<Utc>) -> Vec<(K, DateTime<Utc>, EvictReason)> {
+        let mut evictions = self.inner.recent_evictions();
+        evictions.retain(|(_, at, _)| *at >= since);
+        evictions
+    }
+
+    /// Configuration, size and counters in one struct. The fields are read
+    /// one after another, so under concurrent writes they may be a moment
+    /// apart.
+    pub fn report(&self) -> CacheReport {
+        let mut live_len = 0;
+        self.inner.for_each_live_key(&mut |_| live_len += 1);
+        CacheReport {
+            strategy_name: self.config.strategy.name(),
+            capacity: self.inner.capacity(),
+            ttl: self.inner.ttl(),
+            len: self.inner.len(),
+            live_len,
+            stats: self.inner.stats(),
+        }
+    }
+
+    /// The current `stats` as a JSON object, e.g. for a `/cache/stats`
+    /// endpoint.
+    #[cfg(feature = "serde")]
+    pub fn stats_json(&self) -> String {
+        serde_json::to_string(&self.stats()).expect("CacheStats always serializes")
+    }
+
+    /// Builds a new cache with the same configuration whose live entries are
+    /// this cache's entries with `f` applied to each value. Remaining TTLs are
+    /// carried over.
+    pub fn map_values<W, F>(&self, f: F) -> Rustycache<K, W>
+    where
+        W: 'static + Send + Sync,
+        F: Fn(&V) -> W,
+        V: Clone,
+    {
+        let mut mapped: Rustycache<K, W> = self.config.clone().ttl(self.inner.ttl()).build_unchecked();
+        mapped.key_normalizer = self.key_normalizer.clone();
+        for (key, value, remaining) in self.inner.snapshot_with_meta() {
+            mapped.put_with_ttl(key, f(&value), remaining);
+        }
+        mapped
+    }
+
+    /// `false` if an internal lock is poisoned by a panic or stays held
+    /// across a few brief retries. A supervisor can use it to spot a wedged
+    /// cache and build a fresh one.
+    pub fn is_healthy(&self) -> bool {
+        self.inner.is_healthy()
+    }
+
+    /// Verifies the internal bookkeeping is consistent: `len` within capacity
+    /// and the strategy's ordering structure tracking exactly the stored keys.
+    /// Meant for tests and fuzzing.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.inner.check_invariants()
+    }
+
+    /// Subscribes to insert, eviction, expiration and removal events.
+    /// Slow receivers may observe `RecvError::Lagged`.
+    pub fn subscribe(&self) -> broadcast::Receiver<CacheEvent<K>> {
+        self.inner.subscribe()
     }
 
     pub fn stop_cleaner(&self) {
         self.inner.stop_cleaner()
     }
 
+    /// Empties the cache and stops the background cleaner, returning once
+    /// every cleaner task has exited.
+    pub async fn clear_and_stop(&mut self) {
+        self.inner.clear();
+        self.inner.stop_cleaner();
+        for handle in self.inner.take_cleaners() {
+            handle.abort();
+            let _ = handle.await;
+        }
+    }
+
+    /// Borrows the underlying strategy as its concrete type, e.g.
+    /// `LFUCache<K, V>`, or `None` if it is a different strategy.
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.inner.as_any().downcast_ref()
+    }
+
+    pub(crate) fn take_cleaners(&self) -> Vec<JoinHandle<()>> {
+        self.inner.take_cleaners()
+    }
+
     pub fn start_cleaner(&self, interval: Duration) {
         self.inner.start_cleaner(interval)
     }
@@ -52,12 +1124,288 @@ where
     pub fn len(&self) -> usize {
         self.inner.len()
     }
+
+    /// Entries past their deadline that have not been removed yet. If this
+    /// stays high, the clean interval is long compared to the TTL.
+    pub fn expired_but_present_count(&self) -> usize {
+        self.inner.expired_but_present_count()
+    }
     
+    /// Maximum number of entries the cache was built with.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Default time to live applied by `insert`.
+    pub fn ttl(&self) -> Duration {
+        self.inner.ttl()
+    }
+
+    /// Changes the default TTL from now on. Entries already stored keep the
+    /// deadline they were given; later inserts, and refreshes under a
+    /// sliding policy or `touch_matching`, use `ttl`. A TTL too large for a
+    /// deadline makes new entries permanent.
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.inner.set_ttl(ttl)
+    }
+
+    /// How many more entries fit before a put would evict, or `None` for an
+    /// unbounded cache (capacity `usize::MAX`). A zero-capacity cache stores
+    /// nothing, so it reports `Some(0)`. synth-152 asked for `None` here, but
+    /// that clashes with synth-141, which made capacity 0 mean "store nothing"
+    /// rather than "unbounded"; `None` would tell backpressure logic to batch
+    /// freely into a cache that drops every put.
+    pub fn remaining_capacity(&self) -> Option<usize> {
+        let limit = self.config.options.eviction_timing.write_limit(self.capacity());
+        if limit == usize::MAX {
+            return None;
+        }
+        Some(limit.saturating_sub(self.len()))
+    }
+
     pub fn is_empty(&self) -> bool {
         self.inner.is_empty()
     }
     
+    /// Drops every entry without emitting events. Use `clear_with_callbacks`
+    /// when something downstream must hear about each entry.
     pub fn clear(&mut self) {
+        self.discard_coalesced();
+        self.discard_spilled();
         self.inner.clear()
     }
+
+    /// Like `clear`, but gives up instead of waiting when another thread
+    /// holds the cache lock, e.g. on a shutdown path. Returns whether the
+    /// cache was cleared. Buffered puts are kept when it gives up.
+    pub fn try_clear(&mut self) -> bool {
+        let cleared = self.inner.try_clear();
+        if cleared {
+            self.discard_coalesced();
+            self.discard_spilled();
+        }
+        cleared
+    }
+
+    /// Empties the cache, handing each entry to `f`. Like `drain_expired`,
+    /// this also emits an event per entry: `Removed`, or `Expired` for
+    /// entries already past their deadline.
+    pub fn clear_with_callbacks<F: FnMut(K, V)>(&mut self, mut f: F) {
+        self.discard_coalesced();
+        self.discard_spilled();
+        for (key, value) in self.inner.drain() {
+            f(key, value);
+        }
+    }
+
+    /// Guarantees no entry disappears on its own until `unfreeze`: capacity
+    /// eviction, cleaner sweeps, `evict_expired` and `drain_expired` are
+    /// suspended, and `get` and the other lookups judge expiry as of the
+    /// moment of freezing, so entries whose deadline passes while frozen
+    /// stay visible with the TTL they had left. Explicit `remove`, `clear`
+    /// and `prune_to` still work. While frozen every insert grows the cache,
+    /// so keep the window short. Unfreezing evicts back down to the eviction
+    /// limit at once (the capacity, plus any lazy slack, or `high` with
+    /// watermarks); expired entries go on the next lookup or sweep. Freezing
+    /// does not nest, the first `unfreeze` ends it, and it applies to every
+    /// clone.
+    pub fn freeze(&self) {
+        self.inner.set_frozen(true)
+    }
+
+    pub fn unfreeze(&self) {
+        self.inner.set_frozen(false)
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.inner.is_frozen()
+    }
+
+    /// Stops the cleaner and moves every live entry out as `(key, value,
+    /// expires_at)`, next eviction victim first, ready for `from_entries`,
+    /// e.g. to rebuild an LRU cache as LFU. Permanent entries carry
+    /// `DateTime::<Utc>::MAX_UTC`. Other clones of this handle are left with
+    /// an empty cache.
+    pub fn into_entries(self) -> Vec<(K, V, DateTime<Utc>)> {
+        self.inner.stop_cleaner();
+        let now = self.inner.now();
+        self.inner
+            .drain_entries()
+            .into_iter()
+            .filter(|(_, _, expires_at)| *expires_at > now)
+            .collect()
+    }
+
+    /// Runs one cleaner sweep now and returns how many expired entries were
+    /// dropped. Lets an external scheduler drive caches built with
+    /// `without_cleaner`.
+    pub fn evict_expired(&self) -> usize {
+        self.inner.evict_expired()
+    }
+
+    /// Runs one cleaner tick synchronously at the cache clock's current
+    /// time, for simulations that step a `MockClock` without an async
+    /// runtime. It sweeps exactly like the background task, so
+    /// `cleaner_batch` and `cleaner_time_budget` apply; `evict_expired`
+    /// sweeps everything due regardless.
+    pub fn step_cleaner(&self) {
+        self.inner.step_cleaner();
+    }
+
+    /// Removes and returns every entry whose deadline has passed, i.e. exactly
+    /// what the background cleaner would drop on its next sweep.
+    pub fn drain_expired(&mut self) -> Vec<(K, V)> {
+        self.inner.drain_expired()
+    }
+
+    /// Chooses whether reads extend an entry's life. Entries expire at an
+    /// absolute deadline unless `ExpirationPolicy::Sliding` is selected.
+    pub fn set_expiration_policy(&mut self, policy: ExpirationPolicy) {
+        self.inner.set_expiration_policy(policy)
+    }
+
+    /// Consults `filter` before each capacity eviction; returning `false`
+    /// keeps the entry and the next candidate is tried. If every unpinned
+    /// entry is vetoed the strategy's usual victim is evicted anyway, so the
+    /// cache never outgrows its capacity on the filter's account. `Evicted`
+    /// events still report what was actually evicted. The filter runs under
+    /// the cache lock and must not call back into the cache.
+    pub fn set_eviction_filter<F>(&mut self, filter: F)
+    where
+        F: Fn(&K, &V) -> bool + Send + Sync + 'static,
+    {
+        self.inner.set_eviction_filter(Some(Arc::new(filter)))
+    }
+
+    /// Removes the filter installed by `set_eviction_filter`.
+    pub fn clear_eviction_filter(&mut self) {
+        self.inner.set_eviction_filter(None)
+    }
+
+    /// The expiration policy currently in effect.
+    pub fn expiration_policy(&self) -> ExpirationPolicy {
+        self.inner.expiration_policy()
+    }
+
+    /// Pre-allocates room for `additional` more entries ahead of a bulk load.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional)
+    }
+
+    /// Releases memory held by the internal structures after bulk removals.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit()
+    }
+
+    /// Rebuilds the internal ordering and expiry structures from the map in
+    /// one pass, dropping stale bookkeeping left by churn, then shrinks them.
+    /// A maintenance operation for long-running caches.
+    pub fn compact(&mut self) {
+        self.inner.compact()
+    }
+
+    /// Number of entries the internal map can hold without reallocating.
+    pub fn allocated_capacity(&self) -> usize {
+        self.inner.allocated_capacity()
+    }
+
+    /// Estimated bytes used by the cache's internal structures. Heap data
+    /// owned by keys and values is not included.
+    pub fn estimated_memory(&self) -> usize {
+        self.inner.memory_footprint()
+    }
+
+    /// Time left before `key` expires, `Duration::MAX` for a permanent entry,
+    /// or `None` if the key is absent or already expired.
+    pub fn remaining_ttl(&self, key: &K) -> Option<Duration> {
+        self.inner.remaining_ttl(&self.normalized(key))
+    }
+
+    /// Returns a clone-based, point-in-time snapshot of every live entry
+    /// together with its remaining time to live, `Duration::MAX` for a
+    /// permanent entry as in `remaining_ttl`.
+    pub fn snapshot_with_meta(&self) -> Vec<(K, V, Duration)>
+    where
+        V: Clone,
+    {
+        self.inner.snapshot_with_meta()
+    }
+
+    /// Copies the live entries of `other` into this cache with their remaining
+    /// TTLs, keeping an existing entry if it outlives the incoming one.
+    /// Returns how many entries were copied.
+    pub fn hydrate_from(&mut self, other: &Rustycache<K, V>) -> usize
+    where
+        V: Clone,
+    {
+        self.hydrate_from_with(other, HydrateConflict::default())
+    }
+
+    /// Like `hydrate_from`, with an explicit policy for keys present in both
+    /// caches. Entries are copied shortest-lived first, so when they exceed
+    /// the capacity the longest-lived ones are the ones kept. Copies are not
+    /// forwarded to a write-behind sink.
+    pub fn hydrate_from_with(&mut self, other: &Rustycache<K, V>, conflict: HydrateConflict) -> usize
+    where
+        V: Clone,
+    {
+        let existing: HashMap<K, Duration> = self.inner
+            .snapshot_with_meta()
+            .into_iter()
+            .map(|(key, _, remaining)| (key, remaining))
+            .collect();
+        let mut incoming = other.inner.snapshot_with_meta();
+        incoming.sort_by_key(|(_, _, remaining)| *remaining);
+
+        let mut copied = 0;
+        for (key, value, remaining) in incoming {
+            let key = self.normalize(key);
+            if let Some(&current) = existing.get(&key) {
+                let replace = match conflict {
+                    HydrateConflict::KeepFresher => remaining > current,
+                    HydrateConflict::KeepExisting => false,
+                    HydrateConflict::Overwrite => true,
+                };
+                if !replace {
+                    continue;
+                }
+                self.inner.remove(&key);
+            }
+            let Ok(evicted) = self.inner.put_with_ttl(key.clone(), value, remaining) else {
+                continue;
+            };
+            self.settle_spilled(&key, evicted);
+            copied += 1;
+        }
+        copied
+    }
+
+    /// Clones every live entry into a `HashMap`, taken under a single lock.
+    pub fn to_hashmap(&self) -> HashMap<K, V>
+    where
+        V: Clone,
+    {
+        self.inner
+            .snapshot_with_meta()
+            .into_iter()
+            .map(|(key, value, _)| (key, value))
+            .collect()
+    }
+}
+
+impl<K, V> Rustycache<K, Arc<V>>
+where
+    K: 'static + Send + Sync + Clone + Eq + Hash,
+    V: 'static + Send + Sync,
+{
+    /// Wraps `value` in an `Arc` and inserts it like `insert`.
+    pub fn insert_shared(&mut self, key: K, value: V) {
+        self.insert(key, Arc::new(value))
+    }
+
+    /// Like `get`, spelled out for shared mode: every hit returns a clone of
+    /// the same `Arc`, never a copy of the value.
+    pub fn get_arc(&mut self, key: &K) -> Option<Arc<V>> {
+        self.get(key)
+    }
 }
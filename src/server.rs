@@ -0,0 +1,65 @@
+//! Minimal line-protocol front end for a `Rustycache<String, String>`, for
+//! demos and sidecars. One command per line, one reply line per command:
+//!
+//! - `SET key value` stores `value` (the rest of the line) and replies `OK`.
+//! - `GET key` replies with the value, or `(nil)`.
+//! - `DEL key` replies `1` if a live entry was removed, else `0`.
+//! - `TTL key` replies with the seconds left, `-1` for a permanent entry, or
+//!   `-2` if the key is absent, as Redis does.
+//!
+//! Anything else gets a reply starting with `ERR`.
+
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::rustycache::Rustycache;
+
+/// Accepts connections on `listener` until it fails, serving each on its own
+/// task. All connections share `cache`.
+pub async fn serve(listener: TcpListener, cache: Rustycache<String, String>) -> std::io::Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let cache = cache.clone();
+        tokio::spawn(async move {
+            let _ = handle_connection(stream, cache).await;
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, mut cache: Rustycache<String, String>) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let mut reply = execute(&mut cache, &line);
+        reply.push('\n');
+        writer.write_all(reply.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+fn execute(cache: &mut Rustycache<String, String>, line: &str) -> String {
+    let line = line.trim_end_matches('\r');
+    let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let (key, value) = rest.split_once(' ').unwrap_or((rest, ""));
+    let key = key.to_string();
+    match (command.to_ascii_uppercase().as_str(), key.is_empty(), value.is_empty()) {
+        ("SET", false, false) => {
+            cache.insert(key, value.to_string());
+            "OK".to_string()
+        }
+        ("GET", false, true) => cache.get(&key).unwrap_or_else(|| "(nil)".to_string()),
+        ("DEL", false, true) => match cache.remove(&key) {
+            Some(_) => "1".to_string(),
+            None => "0".to_string(),
+        },
+        ("TTL", false, true) => match cache.remaining_ttl(&key) {
+            None => "-2".to_string(),
+            Some(Duration::MAX) => "-1".to_string(),
+            Some(remaining) => remaining.as_secs().to_string(),
+        },
+        ("SET" | "GET" | "DEL" | "TTL", _, _) => format!("ERR wrong number of arguments for '{}'", command),
+        _ => format!("ERR unknown command '{}'", command),
+    }
+}
@@ -0,0 +1,178 @@
+use crate::strategy::CacheStrategy;
+
+/// A second tier holding entries pushed out of memory by capacity pressure.
+pub(crate) trait Spill<K, V>: Send + Sync {
+    fn spill(&self, key: K, value: V);
+    /// Removes and returns the entry, unless it was spilled longer than
+    /// `ttl` ago.
+    fn take(&self, key: &K, ttl: std::time::Duration) -> Option<V>;
+    fn discard(&self, key: &K);
+    fn discard_all(&self);
+}
+
+/// Puts into `inner`, moving any capacity victim to `spillover` when there
+/// is one. Hands the pair back if `inner` dropped the put.
+pub(crate) fn put_spilling<K: Clone, V>(
+    inner: &dyn CacheStrategy<K, V>,
+    spillover: Option<&dyn Spill<K, V>>,
+    key: K,
+    value: V,
+) -> Result<(), (K, V)> {
+    if spillover.is_none() {
+        return inner.put_evicting(key, value).map(drop);
+    }
+    let evicted = inner.put_evicting(key.clone(), value)?;
+    settle_spilled(spillover, &key, evicted);
+    Ok(())
+}
+
+/// Brings `spillover` in step with a put that stored `key` in memory: the
+/// key's older spilled copy is dropped, so it can't resurface on a later
+/// miss, and every capacity victim of the put is spilled.
+pub(crate) fn settle_spilled<K, V>(spillover: Option<&dyn Spill<K, V>>, key: &K, evicted: Vec<(K, V)>) {
+    let Some(spillover) = spillover else {
+        return;
+    };
+    spillover.discard(key);
+    for (victim, value) in evicted {
+        spillover.spill(victim, value);
+    }
+}
+
+#[cfg(feature = "spillover")]
+pub(crate) use disk::DiskSpillover;
+
+#[cfg(feature = "spillover")]
+mod disk {
+    use std::collections::{BTreeMap, HashMap};
+    use std::fs;
+    use std::hash::Hash;
+    use std::io;
+    use std::marker::PhantomData;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use chrono::{DateTime, Utc};
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    use super::Spill;
+    use crate::strategy::{deadline_after, Clock};
+    use crate::sync::Mutex;
+
+    struct Index<K> {
+        /// File id and spill time per key.
+        files: HashMap<K, (u64, DateTime<Utc>)>,
+        /// Keys by file id, oldest spill first.
+        order: BTreeMap<u64, K>,
+        next_id: u64,
+    }
+
+    /// Spilled values stored as one JSON file each under `dir`, at most
+    /// `capacity` of them; the oldest is dropped to make room. Files are
+    /// deleted when their entry leaves the store or the store is dropped.
+    /// Spill times come from the cache's clock, so they age with its TTL.
+    pub(crate) struct DiskSpillover<K, V> {
+        dir: PathBuf,
+        capacity: usize,
+        clock: Clock,
+        index: Mutex<Index<K>>,
+        _values: PhantomData<fn(V) -> V>,
+    }
+
+    impl<K: Eq + Hash + Clone, V> DiskSpillover<K, V> {
+        pub(crate) fn open(dir: PathBuf, capacity: usize, clock: Clock) -> io::Result<Self> {
+            fs::create_dir_all(&dir)?;
+            Ok(DiskSpillover {
+                dir,
+                capacity,
+                clock,
+                index: Mutex::new(Index { files: HashMap::new(), order: BTreeMap::new(), next_id: 0 }),
+                _values: PhantomData,
+            })
+        }
+
+        fn path(&self, id: u64) -> PathBuf {
+            self.dir.join(format!("{id}.json"))
+        }
+
+        fn unlink(&self, index: &mut Index<K>, key: &K) -> Option<(u64, DateTime<Utc>)> {
+            let (id, spilled_at) = index.files.remove(key)?;
+            index.order.remove(&id);
+            Some((id, spilled_at))
+        }
+    }
+
+    impl<K, V> Spill<K, V> for DiskSpillover<K, V>
+    where
+        K: Eq + Hash + Clone + Send + Sync,
+        V: Serialize + DeserializeOwned,
+    {
+        /// Best effort: a value that fails to serialize or write is dropped,
+        /// as it would be without a spillover tier.
+        fn spill(&self, key: K, value: V) {
+            if self.capacity == 0 {
+                return;
+            }
+            let mut index = self.index.lock();
+            if let Some((id, _)) = self.unlink(&mut index, &key) {
+                let _ = fs::remove_file(self.path(id));
+            }
+            while index.files.len() >= self.capacity {
+                let Some((id, oldest)) = index.order.pop_first() else {
+                    break;
+                };
+                index.files.remove(&oldest);
+                let _ = fs::remove_file(self.path(id));
+            }
+
+            let id = index.next_id;
+            index.next_id += 1;
+            let Ok(bytes) = serde_json::to_vec(&value) else {
+                return;
+            };
+            if fs::write(self.path(id), bytes).is_ok() {
+                index.files.insert(key.clone(), (id, self.clock.now()));
+                index.order.insert(id, key);
+            }
+        }
+
+        fn take(&self, key: &K, ttl: Duration) -> Option<V> {
+            let (id, spilled_at) = {
+                let mut index = self.index.lock();
+                self.unlink(&mut index, key)?
+            };
+            let path = self.path(id);
+            let bytes = fs::read(&path).ok();
+            let _ = fs::remove_file(&path);
+            if deadline_after(spilled_at, ttl) <= self.clock.now() {
+                return None;
+            }
+            serde_json::from_slice(&bytes?).ok()
+        }
+
+        fn discard(&self, key: &K) {
+            let mut index = self.index.lock();
+            if let Some((id, _)) = self.unlink(&mut index, key) {
+                let _ = fs::remove_file(self.path(id));
+            }
+        }
+
+        fn discard_all(&self) {
+            let mut index = self.index.lock();
+            index.files.clear();
+            for id in std::mem::take(&mut index.order).into_keys() {
+                let _ = fs::remove_file(self.path(id));
+            }
+        }
+    }
+
+    impl<K, V> Drop for DiskSpillover<K, V> {
+        fn drop(&mut self) {
+            let index = self.index.lock();
+            for id in index.order.keys() {
+                let _ = fs::remove_file(self.dir.join(format!("{id}.json")));
+            }
+        }
+    }
+}
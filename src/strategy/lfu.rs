@@ -1,217 +1,1205 @@
 use chrono::{DateTime, Utc};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
-use std::sync::{Arc, Mutex};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::sync::Notify;
-use tokio::task;
+use tokio::sync::{broadcast, Notify};
+use tokio::task::{self, JoinHandle};
 use tokio::time::sleep;
-use crate::strategy::CacheStrategy;
+use crate::sync::Mutex;
+use crate::strategy::{deadline_after, extend_deadline, remaining_until, written_within, CacheEvent, CacheItem, CacheOptions, Clock, CacheStats, CacheStrategy, GetResult, EvictionTiming, PromotePolicy, EvictionFilter, EvictReason, EvictionLog, ExpirationPolicy, ExpiryIndex, HashState, BUDGETED_CLEANER_BATCH, EVENT_CHANNEL_CAPACITY, StatsCounters, btree_entry_footprint, hash_map_footprint};
 
 struct CacheEntry<V> {
     value: V,
     expires_at: DateTime<Utc>,
+    inserted_at: DateTime<Utc>,
     frequency: usize,
+    seq: u64,
+    pinned: bool,
+}
+
+/// Keys grouped by frequency. Within a bucket keys are ordered by the
+/// sequence number they were given on entering it, so equal-frequency keys
+/// are evicted first-in, first-out.
+type FreqMap<K> = BTreeMap<usize, BTreeMap<u64, K>>;
+
+fn unlink<K>(freq_map: &mut FreqMap<K>, frequency: usize, seq: u64) {
+    if let Some(bucket) = freq_map.get_mut(&frequency) {
+        bucket.remove(&seq);
+        if bucket.is_empty() {
+            freq_map.remove(&frequency);
+        }
+    }
 }
 
 pub struct LFUCache<K, V>
 where
     K: Eq + Hash + Clone + Send + Sync + 'static,
-    V: Clone + Send + Sync + 'static,
+    V: Send + Sync + 'static,
 {
     capacity: usize,
-    ttl: Duration,
-    map: Arc<Mutex<HashMap<K, CacheEntry<V>>>>,
-    freq_map: Arc<Mutex<BTreeMap<usize, HashSet<K>>>>,
+    ttl: Mutex<Duration>,
+    policy: Mutex<ExpirationPolicy>,
+    options: CacheOptions,
+    map: Arc<Mutex<HashMap<K, CacheEntry<V>, HashState>>>,
+    freq_map: Arc<Mutex<FreqMap<K>>>,
+    expiry: Arc<Mutex<ExpiryIndex<K>>>,
+    eviction_filter: Arc<Mutex<Option<EvictionFilter<K, V>>>>,
+    next_seq: AtomicU64,
+    frozen: Arc<AtomicBool>,
+    /// When the cache was last frozen; liveness is judged at this instant
+    /// while frozen.
+    frozen_at: Mutex<DateTime<Utc>>,
     notify_stop: Arc<Notify>,
+    cleaners: Mutex<Vec<JoinHandle<()>>>,
+    events: broadcast::Sender<CacheEvent<K>>,
+    stats: Arc<StatsCounters>,
+    evictions: Arc<EvictionLog<K>>,
 }
 
 impl<K, V> LFUCache<K, V>
 where
     K: Eq + Hash + Clone + Send + Sync + 'static,
-    V: Clone + Send + Sync + 'static,
+    V: Send + Sync + 'static,
 {
     pub fn new(capacity: usize, ttl: Duration, clean_interval: Duration) -> Self {
-        let cache = LFUCache {
+        Self::with_options(capacity, ttl, clean_interval, CacheOptions::default())
+    }
+
+    /// Builds a cache whose internal map iteration order is reproducible
+    /// across runs.
+    pub fn new_deterministic(capacity: usize, ttl: Duration, clean_interval: Duration) -> Self {
+        let options = CacheOptions { deterministic: true, ..CacheOptions::default() };
+        Self::with_options(capacity, ttl, clean_interval, options)
+    }
+
+    pub fn with_options(capacity: usize, ttl: Duration, clean_interval: Duration, options: CacheOptions) -> Self {
+        let cache = Self::without_cleaner(capacity, ttl, options);
+        cache.start_cleaner(clean_interval);
+        cache
+    }
+
+    /// Builds the cache without spawning a cleaner task; expired entries are
+    /// then dropped on access or by calling `evict_expired`.
+    pub fn without_cleaner(capacity: usize, ttl: Duration, options: CacheOptions) -> Self {
+        let hash_state = HashState::new(options.deterministic);
+        LFUCache::<K, V> {
             capacity,
-            ttl,
-            map: Arc::new(Mutex::new(HashMap::<K, CacheEntry<V>>::new())),
+            ttl: Mutex::new(ttl),
+            policy: Mutex::new(options.expiration_policy),
+            options,
+            map: Arc::new(Mutex::new(HashMap::with_hasher(hash_state.clone()))),
             freq_map: Arc::new(Mutex::new(BTreeMap::new())),
+            expiry: Arc::new(Mutex::new(ExpiryIndex::new(hash_state.clone()))),
+            eviction_filter: Arc::new(Mutex::new(None)),
+            next_seq: AtomicU64::new(0),
+            frozen: Arc::new(AtomicBool::new(false)),
+            frozen_at: Mutex::new(DateTime::<Utc>::MIN_UTC),
             notify_stop: Arc::new(Notify::new()),
+            cleaners: Mutex::new(Vec::new()),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            stats: Arc::new(StatsCounters::default()),
+            evictions: Arc::new(EvictionLog::new()),
+        }
+    }
+
+    /// The instant entries are checked for expiry at: the clock's time, or
+    /// the moment of freezing while frozen, so nothing expires until
+    /// `set_frozen(false)`.
+    fn live_now(&self) -> DateTime<Utc> {
+        if self.is_frozen() {
+            *self.frozen_at.lock()
+        } else {
+            self.now()
+        }
+    }
+
+    /// Number of distinct frequency buckets currently tracked. Never exceeds
+    /// `len`, since empty buckets are dropped.
+    pub fn frequency_buckets(&self) -> usize {
+        self.freq_map.lock().len()
+    }
+
+    /// For each access frequency present, how many live keys have it. A
+    /// heavy count at 1 means the cache is mostly holding one-hit wonders.
+    pub fn frequency_histogram(&self) -> BTreeMap<usize, usize> {
+        let map = self.map.lock();
+        let now = self.live_now();
+        let mut histogram = BTreeMap::new();
+        for entry in map.values().filter(|entry| entry.expires_at > now) {
+            *histogram.entry(entry.frequency).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Number of distinct deadlines in the expiry index. Never exceeds `len`,
+    /// since removing an entry also drops its slot.
+    pub fn expiry_buckets(&self) -> usize {
+        self.expiry.lock().bucket_count()
+    }
+
+    /// Sets every entry's frequency back to 1, leaving values and deadlines
+    /// untouched, e.g. to recover from a skewed access pattern. Entries keep
+    /// their relative eviction order within the single remaining bucket.
+    pub fn reset_frequencies(&self) {
+        let mut map = self.map.lock();
+        let mut freq_map = self.freq_map.lock();
+        let mut bucket = BTreeMap::new();
+        for key in std::mem::take(&mut *freq_map).into_values().flat_map(BTreeMap::into_values) {
+            if let Some(entry) = map.get_mut(&key) {
+                entry.frequency = 1;
+                entry.seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+                bucket.insert(entry.seq, key);
+            }
+        }
+        if !bucket.is_empty() {
+            freq_map.insert(1, bucket);
+        }
+    }
+
+    /// Access count of a live entry, without counting this call as an access.
+    pub fn frequency(&self, key: &K) -> Option<usize> {
+        let now = self.live_now();
+        self.map.lock().get(key).filter(|entry| entry.expires_at > now).map(|entry| entry.frequency)
+    }
+
+    fn insert_entry(&self, key: K, value: V, expires_at: DateTime<Utc>) -> Result<Vec<(K, V)>, (K, V)> {
+        let mut events = Vec::new();
+        let evicted = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            if let Some(limit) = self.options.amortized_cleanup.filter(|_| !self.is_frozen()) {
+                let expired = Self::take_expired(&mut map, &mut freq_map, &mut self.expiry.lock(), self.now(), limit);
+                events.extend(expired.into_iter().map(|(key, _)| CacheEvent::Expired(key)));
+            }
+            self.insert_locked(&mut map, &mut freq_map, key, value, expires_at, &mut events)
         };
+        self.emit(events);
+        evicted
+    }
 
-        let map_clone = Arc::clone(&cache.map);
-        let freq_map_clone = Arc::clone(&cache.freq_map);
-        let notify_clone = Arc::clone(&cache.notify_stop);
+    fn insert_locked(
+        &self,
+        map: &mut HashMap<K, CacheEntry<V>, HashState>,
+        freq_map: &mut FreqMap<K>,
+        key: K,
+        value: V,
+        expires_at: DateTime<Utc>,
+        events: &mut Vec<CacheEvent<K>>,
+    ) -> Result<Vec<(K, V)>, (K, V)> {
+        if self.capacity == 0 {
+            return Err((key, value)); // a zero-capacity cache stores nothing
+        }
 
-        task::spawn(async move {
-            loop {
-                tokio::select! {
-                    _ = sleep(clean_interval) => {
-                        let now = Utc::now();
-                        let mut map = map_clone.lock().unwrap();
-                        let mut freq_map = freq_map_clone.lock().unwrap();
-                        let keys_to_remove: Vec<K> = map.iter()
-                            .filter_map(|(k, v)| {
-                                if v.expires_at <= now {
-                                    Some(k.clone())
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect();
-
-                        for key in keys_to_remove {
-                            if let Some(entry) = map.remove(&key) {
-                                if let Some(set) = freq_map.get_mut(&entry.frequency) {
-                                    set.remove(&key);
-                                    if set.is_empty() {
-                                        freq_map.remove(&entry.frequency);
-                                    }
-                                }
-                            }
-                        }
-                    },
-                    _ = notify_clone.notified() => break,
+        let mut evicted = Vec::new();
+        let mut expiry = self.expiry.lock();
+        if let Some(entry) = map.get_mut(&key) {
+            entry.value = value;
+            expiry.reschedule(entry.expires_at, expires_at, &key);
+            entry.expires_at = expires_at;
+            entry.inserted_at = self.now();
+            events.push(CacheEvent::Inserted(key));
+        } else {
+            if !self.is_frozen() && map.len() >= self.options.eviction_timing.write_limit(self.capacity) {
+                let target = self.options.eviction_timing.evict_target(self.capacity);
+                let filter = self.eviction_filter.lock().clone();
+                while map.len() > target {
+                    let Some((victim, value)) = Self::evict_one(map, freq_map, &mut expiry, filter.as_ref()) else {
+                        break;
+                    };
+                    events.push(CacheEvent::Evicted(victim.clone()));
+                    evicted.push((victim, value));
                 }
             }
-        });
 
-        cache
+            let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+            map.insert(key.clone(), CacheEntry {
+                value,
+                expires_at,
+                inserted_at: self.now(),
+                frequency: 1,
+                seq,
+                pinned: false,
+            });
+
+            freq_map.entry(1).or_default().insert(seq, key.clone());
+            expiry.schedule(expires_at, key.clone());
+            events.push(CacheEvent::Inserted(key));
+        }
+        Ok(evicted)
+    }
+
+    /// Evicts the entry in the slot chosen by `victim_slot`.
+    fn evict_one(
+        map: &mut HashMap<K, CacheEntry<V>, HashState>,
+        freq_map: &mut FreqMap<K>,
+        expiry: &mut ExpiryIndex<K>,
+        filter: Option<&EvictionFilter<K, V>>,
+    ) -> Option<(K, V)> {
+        let (frequency, seq) = Self::victim_slot(map, freq_map, filter)?;
+        let k = freq_map.get(&frequency)?.get(&seq)?.clone();
+        let entry = Self::detach(map, freq_map, expiry, &k)?;
+        Some((k, entry.value))
+    }
+
+    /// Removes `key` from the map together with its frequency and expiry
+    /// slots.
+    fn detach(
+        map: &mut HashMap<K, CacheEntry<V>, HashState>,
+        freq_map: &mut FreqMap<K>,
+        expiry: &mut ExpiryIndex<K>,
+        key: &K,
+    ) -> Option<CacheEntry<V>> {
+        let entry = map.remove(key)?;
+        unlink(freq_map, entry.frequency, entry.seq);
+        expiry.unschedule(entry.expires_at, key);
+        Some(entry)
+    }
+
+    /// The least frequently used slot that is neither pinned nor vetoed by
+    /// `filter`, or the least frequently used unpinned one if `filter` vetoes
+    /// them all.
+    fn victim_slot(
+        map: &HashMap<K, CacheEntry<V>, HashState>,
+        freq_map: &FreqMap<K>,
+        filter: Option<&EvictionFilter<K, V>>,
+    ) -> Option<(usize, u64)> {
+        let first_slot = |accept: &dyn Fn(&K, &CacheEntry<V>) -> bool| {
+            freq_map.iter().find_map(|(&frequency, bucket)| {
+                bucket.iter()
+                    .find(|(_, key)| map.get(*key).is_some_and(|entry| !entry.pinned && accept(key, entry)))
+                    .map(|(&seq, _)| (frequency, seq))
+            })
+        };
+        filter
+            .and_then(|filter| first_slot(&|key, entry| filter(key, &entry.value)))
+            .or_else(|| first_slot(&|_, _| true))
+    }
+
+    fn lookup(&self, key: &K, max_age: Option<Duration>) -> Option<V>
+    where
+        V: Clone,
+    {
+        let mut events = Vec::new();
+        let value = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            self.lookup_locked(&mut map, &mut freq_map, key, max_age, &mut events)
+        };
+        self.emit(events);
+        value
+    }
+
+    fn lookup_locked(
+        &self,
+        map: &mut HashMap<K, CacheEntry<V>, HashState>,
+        freq_map: &mut FreqMap<K>,
+        key: &K,
+        max_age: Option<Duration>,
+        events: &mut Vec<CacheEvent<K>>,
+    ) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.probe_locked(map, freq_map, key, max_age, true, events, V::clone).into_option()
+    }
+
+    /// Applies the expiration policy to an accessed entry, keeping the expiry
+    /// index in step when a sliding policy moves the deadline.
+    fn on_access(&self, key: &K, expires_at: &mut DateTime<Utc>) {
+        let before = *expires_at;
+        let ttl = *self.ttl.lock();
+        self.policy.lock().on_access(expires_at, self.now(), ttl);
+        if *expires_at != before {
+            self.expiry.lock().reschedule(before, *expires_at, key);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn probe_locked<R>(
+        &self,
+        map: &mut HashMap<K, CacheEntry<V>, HashState>,
+        freq_map: &mut FreqMap<K>,
+        key: &K,
+        max_age: Option<Duration>,
+        promote: bool,
+        events: &mut Vec<CacheEvent<K>>,
+        read: impl FnOnce(&V) -> R,
+    ) -> GetResult<R> {
+        let now = self.live_now();
+        let Some(entry) = map.get_mut(key) else {
+            self.stats.miss();
+            return GetResult::Missing;
+        };
+        if entry.expires_at <= now {
+            Self::detach(map, freq_map, &mut self.expiry.lock(), key);
+            events.push(CacheEvent::Expired(key.clone()));
+            self.stats.miss();
+            return GetResult::Expired;
+        }
+
+        if !written_within(entry.inserted_at, now, max_age) {
+            self.stats.miss();
+            return GetResult::Expired;
+        }
+
+        self.stats.hit();
+
+        self.on_access(key, &mut entry.expires_at);
+        if promote {
+            unlink(freq_map, entry.frequency, entry.seq);
+            entry.frequency += 1;
+            entry.seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+            freq_map.entry(entry.frequency).or_default().insert(entry.seq, key.clone());
+        }
+
+        GetResult::Hit(read(&entry.value))
+    }
+
+    fn take_expired(
+        map: &mut HashMap<K, CacheEntry<V>, HashState>,
+        freq_map: &mut FreqMap<K>,
+        expiry: &mut ExpiryIndex<K>,
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Vec<(K, V)> {
+        let mut expired = Vec::new();
+        while expired.len() < limit {
+            let Some((deadline, key)) = expiry.pop_due(now) else {
+                break;
+            };
+            if map.get(&key).is_some_and(|entry| entry.expires_at == deadline) {
+                if let Some(entry) = map.remove(&key) {
+                    unlink(freq_map, entry.frequency, entry.seq);
+                    expired.push((key, entry.value));
+                }
+            }
+        }
+        expired
+    }
+
+    fn trim_to(
+        map: &mut HashMap<K, CacheEntry<V>, HashState>,
+        freq_map: &mut FreqMap<K>,
+        expiry: &mut ExpiryIndex<K>,
+        target_len: usize,
+        filter: Option<&EvictionFilter<K, V>>,
+    ) -> Vec<K> {
+        let mut evicted = Vec::new();
+        while map.len() > target_len {
+            match Self::evict_one(map, freq_map, expiry, filter) {
+                Some((key, _)) => evicted.push(key),
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    fn emit(&self, events: Vec<CacheEvent<K>>) {
+        for event in events {
+            self.stats.record(&event);
+            self.evictions.record(&event, self.now());
+            let _ = self.events.send(event);
+        }
+    }
+
+    fn sweeper(&self) -> Sweeper<K, V> {
+        let time_budget = self.options.cleaner_time_budget;
+        Sweeper {
+            map: Arc::clone(&self.map),
+            freq_map: Arc::clone(&self.freq_map),
+            expiry: Arc::clone(&self.expiry),
+            eviction_filter: Arc::clone(&self.eviction_filter),
+            frozen: Arc::clone(&self.frozen),
+            events: self.events.clone(),
+            stats: Arc::clone(&self.stats),
+            evictions: Arc::clone(&self.evictions),
+            clock: self.options.clock.clone(),
+            capacity: self.capacity,
+            lazy_eviction: matches!(self.options.eviction_timing, EvictionTiming::Lazy { .. }),
+            batch: self.options.cleaner_batch
+                .or(time_budget.map(|_| BUDGETED_CLEANER_BATCH))
+                .unwrap_or(usize::MAX)
+                .max(1),
+            time_budget,
+        }
     }
 }
 
-impl<K, V> CacheStrategy<K, V> for LFUCache<K, V>
+/// What an expiry sweep touches, cloned out of the cache so the background
+/// cleaner can own it. The cleaner task, `step_cleaner` and `evict_expired`
+/// all sweep through here.
+struct Sweeper<K, V> {
+    map: Arc<Mutex<HashMap<K, CacheEntry<V>, HashState>>>,
+    freq_map: Arc<Mutex<FreqMap<K>>>,
+    expiry: Arc<Mutex<ExpiryIndex<K>>>,
+    eviction_filter: Arc<Mutex<Option<EvictionFilter<K, V>>>>,
+    frozen: Arc<AtomicBool>,
+    events: broadcast::Sender<CacheEvent<K>>,
+    stats: Arc<StatsCounters>,
+    evictions: Arc<EvictionLog<K>>,
+    clock: Clock,
+    capacity: usize,
+    lazy_eviction: bool,
+    batch: usize,
+    time_budget: Option<Duration>,
+}
+
+impl<K, V> Sweeper<K, V>
 where
     K: Eq + Hash + Clone + Send + Sync + 'static,
-    V: Clone + Send + Sync + 'static,
+    V: Send + Sync + 'static,
 {
-    fn put(&mut self, key: K, value: V) {
-        let mut map = self.map.lock().unwrap();
-        let mut freq_map = self.freq_map.lock().unwrap();
+    /// One background cleaner tick. Like `sweep` with the configured batch
+    /// size and time budget, but yields between batches so other tasks get
+    /// a turn.
+    async fn tick(&self) {
+        if self.frozen.load(Ordering::Acquire) {
+            return;
+        }
+        let started = self.clock.now();
+        while !self.expire_batch(self.batch).1 && !self.budget_spent(self.time_budget, started) {
+            task::yield_now().await;
+        }
+        self.trim();
+    }
 
-        if let Some(entry) = map.get_mut(&key) {
-            entry.value = value;
-            entry.expires_at = Utc::now() + chrono::Duration::from_std(self.ttl).unwrap();
+    /// Removes expired entries `batch` at a time until none are due or
+    /// `time_budget` is spent, then trims back to capacity under lazy
+    /// eviction. Returns how many expired. Does nothing while frozen.
+    fn sweep(&self, batch: usize, time_budget: Option<Duration>) -> usize {
+        if self.frozen.load(Ordering::Acquire) {
+            return 0;
+        }
+        let started = self.clock.now();
+        let mut count = 0;
+        loop {
+            let (expired, done) = self.expire_batch(batch);
+            count += expired;
+            if done || self.budget_spent(time_budget, started) {
+                break;
+            }
+        }
+        self.trim();
+        count
+    }
+
+    /// Removes up to `batch` expired entries. Returns how many, and whether
+    /// that cleared the backlog.
+    fn expire_batch(&self, batch: usize) -> (usize, bool) {
+        let expired = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            LFUCache::take_expired(&mut map, &mut freq_map, &mut self.expiry.lock(), self.clock.now(), batch)
+        };
+        let count = expired.len();
+        self.emit(expired.into_iter().map(|(key, _)| CacheEvent::Expired(key)));
+        (count, count < batch)
+    }
+
+    /// Whether `time_budget` has run out since `started`, by the cache
+    /// clock.
+    fn budget_spent(&self, time_budget: Option<Duration>, started: DateTime<Utc>) -> bool {
+        time_budget.is_some_and(|budget| (self.clock.now() - started).to_std().is_ok_and(|elapsed| elapsed >= budget))
+    }
+
+    fn trim(&self) {
+        if !self.lazy_eviction {
             return;
         }
+        let evicted = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            LFUCache::trim_to(&mut map, &mut freq_map, &mut self.expiry.lock(), self.capacity, self.eviction_filter.lock().clone().as_ref())
+        };
+        self.emit(evicted.into_iter().map(CacheEvent::Evicted));
+    }
 
-        if map.len() >= self.capacity {
-            if let Some((&min_freq, keys)) = freq_map.iter_mut().next() {
-                if let Some(k) = keys.iter().next().cloned() {
-                    keys.remove(&k);
-                    if keys.is_empty() {
-                        freq_map.remove(&min_freq);
-                    }
-                    map.remove(&k);
-                }
+    fn emit(&self, events: impl IntoIterator<Item = CacheEvent<K>>) {
+        for event in events {
+            self.stats.record(&event);
+            self.evictions.record(&event, self.clock.now());
+            let _ = self.events.send(event);
+        }
+    }
+}
+
+impl<K, V> CacheStrategy<K, V> for LFUCache<K, V>
+where
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Send + Sync + 'static,
+{
+    fn put(&self, key: K, value: V) {
+        let expires_at = deadline_after(self.now(), *self.ttl.lock());
+        let _ = self.insert_entry(key, value, expires_at);
+    }
+
+    fn put_evicting(&self, key: K, value: V) -> Result<Vec<(K, V)>, (K, V)> {
+        let expires_at = deadline_after(self.now(), *self.ttl.lock());
+        self.insert_entry(key, value, expires_at)
+    }
+
+    fn restore(&self, entries: &mut dyn Iterator<Item = (K, V, DateTime<Utc>)>) {
+        let mut events = Vec::new();
+        {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            let now = self.now();
+            for (key, value, expires_at) in entries.filter(|(_, _, expires_at)| *expires_at > now) {
+                let _ = self.insert_locked(&mut map, &mut freq_map, key, value, expires_at, &mut events);
             }
         }
+        self.emit(events);
+    }
 
-        map.insert(key.clone(), CacheEntry {
-            value,
-            expires_at: Utc::now() + chrono::Duration::from_std(self.ttl).unwrap(),
-            frequency: 1,
-        });
+    fn put_permanent(&self, key: K, value: V) -> Result<Vec<(K, V)>, (K, V)> {
+        self.insert_entry(key, value, DateTime::<Utc>::MAX_UTC)
+    }
 
-        freq_map.entry(1).or_insert_with(HashSet::new).insert(key);
+    fn put_with_ttl(&self, key: K, value: V, ttl: Duration) -> Result<Vec<(K, V)>, (K, V)> {
+        self.insert_entry(key, value, deadline_after(self.now(), ttl))
     }
 
-    fn get(&mut self, key: &K) -> Option<V> {
-        let mut map = self.map.lock().unwrap();
-        let mut freq_map = self.freq_map.lock().unwrap();
+    fn try_put(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let mut events = Vec::new();
+        {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            if !map.contains_key(&key) && map.len() >= self.options.eviction_timing.write_limit(self.capacity) {
+                return Err((key, value));
+            }
+            let _ = self.insert_locked(&mut map, &mut freq_map, key, value, deadline_after(self.now(), *self.ttl.lock()), &mut events);
+        }
+        self.emit(events);
+        Ok(())
+    }
 
-        if let Some(entry) = map.get_mut(key) {
-            if entry.expires_at <= Utc::now() {
-                let freq = entry.frequency;
-                map.remove(key);
-                if let Some(set) = freq_map.get_mut(&freq) {
-                    set.remove(key);
-                    if set.is_empty() {
-                        freq_map.remove(&freq);
-                    }
+    fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, None)
+    }
+
+    fn get_with(&self, key: &K, f: &mut dyn FnMut(&V)) {
+        let mut events = Vec::new();
+        {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            self.probe_locked(&mut map, &mut freq_map, key, None, true, &mut events, |value| f(value));
+        }
+        self.emit(events);
+    }
+
+    fn get_or_insert(&self, key: K, default: V) -> (V, Vec<(K, V)>)
+    where
+        V: Clone,
+    {
+        let mut events = Vec::new();
+        let value = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            match self.lookup_locked(&mut map, &mut freq_map, &key, None, &mut events) {
+                Some(value) => (value, Vec::new()),
+                None => {
+                    let expires_at = deadline_after(self.now(), *self.ttl.lock());
+                    let evicted = self.insert_locked(&mut map, &mut freq_map, key, default.clone(), expires_at, &mut events).unwrap_or_default();
+                    (default, evicted)
                 }
-                return None;
             }
+        };
+        self.emit(events);
+        value
+    }
 
-            let old_freq = entry.frequency;
-            entry.frequency += 1;
-
-            if let Some(set) = freq_map.get_mut(&old_freq) {
-                set.remove(key);
-                if set.is_empty() {
-                    freq_map.remove(&old_freq);
+    fn put_if_absent_with_ttl(&self, key: K, value: V, ttl: Duration) -> Option<Vec<(K, V)>> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let mut events = Vec::new();
+        let inserted = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            match map.get(&key) {
+                Some(entry) if entry.expires_at > self.live_now() => None,
+                Some(_) => {
+                    Self::detach(&mut map, &mut freq_map, &mut self.expiry.lock(), &key);
+                    events.push(CacheEvent::Expired(key.clone()));
+                    self.insert_locked(&mut map, &mut freq_map, key, value, deadline_after(self.now(), ttl), &mut events).ok()
+                }
+                None => {
+                    self.insert_locked(&mut map, &mut freq_map, key, value, deadline_after(self.now(), ttl), &mut events).ok()
                 }
             }
+        };
+        self.emit(events);
+        inserted
+    }
+
+    fn get_detailed(&self, key: &K) -> GetResult<V>
+    where
+        V: Clone,
+    {
+        let mut events = Vec::new();
+        let result = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            self.probe_locked(&mut map, &mut freq_map, key, None, true, &mut events, V::clone)
+        };
+        self.emit(events);
+        result
+    }
+
+    fn get_if_fresh_within(&self, key: &K, max_age: Duration) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, Some(max_age))
+    }
+
+    fn get_key_value(&self, key: &K) -> Option<(K, V)>
+    where
+        V: Clone,
+    {
+        let mut events = Vec::new();
+        let pair = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            self.lookup_locked(&mut map, &mut freq_map, key, None, &mut events)
+                .and_then(|value| map.get_key_value(key).map(|(stored, _)| (stored.clone(), value)))
+        };
+        self.emit(events);
+        pair
+    }
 
-            freq_map
-                .entry(entry.frequency)
-                .or_insert_with(HashSet::new)
-                .insert(key.clone());
+    fn get_with_metadata(&self, key: &K) -> Option<CacheItem<V>>
+    where
+        V: Clone,
+    {
+        let mut events = Vec::new();
+        let item = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            self.lookup_locked(&mut map, &mut freq_map, key, None, &mut events).and_then(|value| {
+                map.get(key).map(|entry| CacheItem::new(value, entry.inserted_at, entry.expires_at, Some(entry.frequency), self.now()))
+            })
+        };
+        self.emit(events);
+        item
+    }
+
+    fn get_group(&self, keys: &[K], promote: PromotePolicy) -> Vec<Option<V>>
+    where
+        V: Clone,
+    {
+        let mut events = Vec::new();
+        let values = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            keys.iter()
+                .enumerate()
+                .map(|(index, key)| {
+                    let promote = promote.promotes(index, keys.len());
+                    self.probe_locked(&mut map, &mut freq_map, key, None, promote, &mut events, V::clone).into_option()
+                })
+                .collect()
+        };
+        self.emit(events);
+        values
+    }
+
+    fn get_consistent(&self, keys: &[K]) -> Vec<Option<V>>
+    where
+        V: Clone,
+    {
+        let mut events = Vec::new();
+        let values = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            keys.iter()
+                .map(|key| self.lookup_locked(&mut map, &mut freq_map, key, None, &mut events))
+                .collect()
+        };
+        self.emit(events);
+        values
+    }
 
-            return Some(entry.value.clone());
+    fn remove(&self, key: &K) {
+        let removed = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            Self::detach(&mut map, &mut freq_map, &mut self.expiry.lock(), key).is_some()
+        };
+        if removed {
+            self.emit(vec![CacheEvent::Removed(key.clone())]);
         }
+    }
 
-        None
+    fn take(&self, key: &K) -> Option<V> {
+        let entry = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            Self::detach(&mut map, &mut freq_map, &mut self.expiry.lock(), key)?
+        };
+        if entry.expires_at > self.live_now() {
+            self.emit(vec![CacheEvent::Removed(key.clone())]);
+            Some(entry.value)
+        } else {
+            self.emit(vec![CacheEvent::Expired(key.clone())]);
+            None
+        }
     }
 
-    fn remove(&mut self, key: &K) {
-        let mut map = self.map.lock().unwrap();
-        let mut freq_map = self.freq_map.lock().unwrap();
+    fn remove_many(&self, keys: &[K]) -> Vec<Option<V>> {
+        let now = self.live_now();
+        let mut events = Vec::new();
+        let values = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            let mut expiry = self.expiry.lock();
+            keys.iter()
+                .map(|key| {
+                    let entry = Self::detach(&mut map, &mut freq_map, &mut expiry, key)?;
+                    if entry.expires_at > now {
+                        events.push(CacheEvent::Removed(key.clone()));
+                        Some(entry.value)
+                    } else {
+                        events.push(CacheEvent::Expired(key.clone()));
+                        None
+                    }
+                })
+                .collect()
+        };
+        self.emit(events);
+        values
+    }
+
+    fn extend_ttl(&self, key: &K, by: Duration) -> bool {
+        let mut map = self.map.lock();
+        match map.get_mut(key) {
+            Some(entry) if entry.expires_at > self.live_now() => {
+                let before = entry.expires_at;
+                extend_deadline(&mut entry.expires_at, by);
+                self.expiry.lock().reschedule(before, entry.expires_at, key);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn prune_to(&self, target_len: usize) -> usize {
+        let evicted = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            Self::trim_to(&mut map, &mut freq_map, &mut self.expiry.lock(), target_len, self.eviction_filter.lock().clone().as_ref())
+        };
+        let count = evicted.len();
+        self.emit(evicted.into_iter().map(CacheEvent::Evicted).collect());
+        count
+    }
+
+    fn touch_matching(&self, pred: &dyn Fn(&K) -> bool) -> usize {
+        let mut map = self.map.lock();
+        let mut expiry = self.expiry.lock();
+        let now = self.live_now();
+        let refreshed = deadline_after(self.now(), *self.ttl.lock());
+        let mut count = 0;
+        for (key, entry) in map.iter_mut() {
+            if entry.expires_at > now && entry.expires_at != DateTime::<Utc>::MAX_UTC && pred(key) {
+                expiry.reschedule(entry.expires_at, refreshed, key);
+                entry.expires_at = refreshed;
+                count += 1;
+            }
+        }
+        count
+    }
 
-        if let Some(entry) = map.remove(key) {
-            if let Some(set) = freq_map.get_mut(&entry.frequency) {
-                set.remove(key);
-                if set.is_empty() {
-                    freq_map.remove(&entry.frequency);
+    fn contains_and_refresh(&self, key: &K) -> bool {
+        let mut events = Vec::new();
+        let found = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            let now = self.live_now();
+            match map.get_mut(key) {
+                Some(entry) if entry.expires_at > now => {
+                    if entry.expires_at != DateTime::<Utc>::MAX_UTC {
+                        let refreshed = deadline_after(self.now(), *self.ttl.lock());
+                        self.expiry.lock().reschedule(entry.expires_at, refreshed, key);
+                        entry.expires_at = refreshed;
+                    }
+                    unlink(&mut freq_map, entry.frequency, entry.seq);
+                    entry.frequency += 1;
+                    entry.seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+                    freq_map.entry(entry.frequency).or_default().insert(entry.seq, key.clone());
+                    true
+                }
+                Some(_) => {
+                    Self::detach(&mut map, &mut freq_map, &mut self.expiry.lock(), key);
+                    events.push(CacheEvent::Expired(key.clone()));
+                    false
                 }
+                None => false,
+            }
+        };
+        self.emit(events);
+        found
+    }
+
+    fn update_many(&self, keys: &[K], f: &mut dyn FnMut(&K, &mut V)) {
+        let mut events = Vec::new();
+        {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            let now = self.live_now();
+            for key in keys {
+                let Some(entry) = map.get_mut(key).filter(|entry| entry.expires_at > now) else {
+                    continue;
+                };
+                self.on_access(key, &mut entry.expires_at);
+                f(key, &mut entry.value);
+                events.push(CacheEvent::Updated(key.clone()));
+                unlink(&mut freq_map, entry.frequency, entry.seq);
+                entry.frequency += 1;
+                entry.seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+                freq_map.entry(entry.frequency).or_default().insert(entry.seq, key.clone());
+            }
+        }
+        self.emit(events);
+    }
+
+    fn pin(&self, key: &K) -> bool {
+        let mut map = self.map.lock();
+        let now = self.live_now();
+        match map.get_mut(key) {
+            Some(entry) if entry.expires_at > now => {
+                entry.pinned = true;
+                true
             }
+            _ => false,
+        }
+    }
+
+    fn unpin(&self, key: &K) {
+        if let Some(entry) = self.map.lock().get_mut(key) {
+            entry.pinned = false;
         }
     }
 
+    fn peek_victim(&self) -> Option<K> {
+        let map = self.map.lock();
+        let freq_map = self.freq_map.lock();
+        let filter = self.eviction_filter.lock().clone();
+        let (frequency, seq) = Self::victim_slot(&map, &freq_map, filter.as_ref())?;
+        freq_map.get(&frequency)?.get(&seq).cloned()
+    }
+
+    fn debug_order(&self) -> Vec<K> {
+        let freq_map = self.freq_map.lock();
+        freq_map.values().flat_map(|bucket| bucket.values().cloned()).collect()
+    }
+
     fn contains(&self, key: &K) -> bool {
-        let map = self.map.lock().unwrap();
+        let map = self.map.lock();
         map.contains_key(key)
     }
 
     fn len(&self) -> usize {
-        let map = self.map.lock().unwrap();
+        let map = self.map.lock();
         map.len()
     }
+    fn expired_but_present_count(&self) -> usize {
+        let map = self.map.lock();
+        let now = self.live_now();
+        map.values().filter(|entry| entry.expires_at <= now).count()
+    }
     fn is_empty(&self) -> bool {
-        let map = self.map.lock().unwrap();
+        let map = self.map.lock();
         map.is_empty()
     }
-    fn clear(&mut self) {
-        let mut map = self.map.lock().unwrap();
-        let mut freq_map = self.freq_map.lock().unwrap();
+    fn clear(&self) {
+        let mut map = self.map.lock();
+        let mut freq_map = self.freq_map.lock();
         map.clear();
         freq_map.clear();
+        self.expiry.lock().clear();
+    }
+
+    fn try_clear(&self) -> bool {
+        let Some(mut map) = self.map.try_lock() else {
+            return false;
+        };
+        let Some(mut freq_map) = self.freq_map.try_lock() else {
+            return false;
+        };
+        let Some(mut expiry) = self.expiry.try_lock() else {
+            return false;
+        };
+        map.clear();
+        freq_map.clear();
+        expiry.clear();
+        true
+    }
+
+    fn drain(&self) -> Vec<(K, V)> {
+        let now = self.live_now();
+        let (entries, events): (Vec<_>, Vec<_>) = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            freq_map.clear();
+            self.expiry.lock().clear();
+            map.drain()
+                .map(|(key, entry)| {
+                    let event = if entry.expires_at > now {
+                        CacheEvent::Removed(key.clone())
+                    } else {
+                        CacheEvent::Expired(key.clone())
+                    };
+                    ((key, entry.value), event)
+                })
+                .unzip()
+        };
+        self.emit(events);
+        entries
+    }
+
+    fn drain_entries(&self) -> Vec<(K, V, DateTime<Utc>)> {
+        let now = self.live_now();
+        let mut events = Vec::new();
+        let entries = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            self.expiry.lock().clear();
+            std::mem::take(&mut *freq_map).into_values().flat_map(BTreeMap::into_values)
+                .filter_map(|key| {
+                    let entry = map.remove(&key)?;
+                    events.push(if entry.expires_at > now {
+                        CacheEvent::Removed(key.clone())
+                    } else {
+                        CacheEvent::Expired(key.clone())
+                    });
+                    Some((key, entry.value, entry.expires_at))
+                })
+                .collect()
+        };
+        self.emit(events);
+        entries
+    }
+
+    fn evict_expired(&self) -> usize {
+        self.sweeper().sweep(usize::MAX, None)
+    }
+
+    fn step_cleaner(&self) {
+        let sweeper = self.sweeper();
+        sweeper.sweep(sweeper.batch, sweeper.time_budget);
+    }
+
+    fn drain_expired(&self) -> Vec<(K, V)> {
+        if self.is_frozen() {
+            return Vec::new();
+        }
+        let expired = {
+            let mut map = self.map.lock();
+            let mut freq_map = self.freq_map.lock();
+            Self::take_expired(&mut map, &mut freq_map, &mut self.expiry.lock(), self.now(), usize::MAX)
+        };
+        self.emit(expired.iter().map(|(key, _)| CacheEvent::Expired(key.clone())).collect());
+        expired
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.options.clock.now()
+    }
+
+    fn ttl(&self) -> Duration {
+        *self.ttl.lock()
+    }
+
+    fn set_ttl(&self, ttl: Duration) {
+        *self.ttl.lock() = ttl;
+    }
+
+    fn set_expiration_policy(&self, policy: ExpirationPolicy) {
+        *self.policy.lock() = policy;
+    }
+
+    fn set_frozen(&self, frozen: bool) {
+        if frozen && !self.is_frozen() {
+            *self.frozen_at.lock() = self.now();
+        }
+        self.frozen.store(frozen, Ordering::Release);
+        if !frozen {
+            self.prune_to(self.options.eviction_timing.write_limit(self.capacity));
+        }
+    }
+
+    fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::Acquire)
+    }
+
+    fn set_eviction_filter(&self, filter: Option<EvictionFilter<K, V>>) {
+        *self.eviction_filter.lock() = filter;
+    }
+
+    fn expiration_policy(&self) -> ExpirationPolicy {
+        *self.policy.lock()
+    }
+
+    fn reserve(&self, additional: usize) {
+        let mut map = self.map.lock();
+        map.reserve(additional);
+    }
+
+    fn shrink_to_fit(&self) {
+        let mut map = self.map.lock();
+        map.shrink_to_fit();
+    }
+
+    fn compact(&self) {
+        let mut map = self.map.lock();
+        let mut freq_map = self.freq_map.lock();
+        freq_map.clear();
+        for (key, entry) in map.iter() {
+            freq_map.entry(entry.frequency).or_default().insert(entry.seq, key.clone());
+        }
+        map.shrink_to_fit();
+        self.expiry.lock().rebuild(map.iter().map(|(key, entry)| (entry.expires_at, key.clone())));
+    }
+
+    fn allocated_capacity(&self) -> usize {
+        let map = self.map.lock();
+        map.capacity()
+    }
+
+    fn memory_footprint(&self) -> usize {
+        let map = self.map.lock();
+        let freq_map = self.freq_map.lock();
+        let expiry = self.expiry.lock();
+        let buckets: usize = freq_map.values()
+            .map(|bucket| btree_entry_footprint::<usize, BTreeMap<u64, K>>() + bucket.len() * btree_entry_footprint::<u64, K>())
+            .sum();
+        size_of::<Self>() + hash_map_footprint(&map) + buckets + expiry.footprint()
+    }
+
+    fn remaining_ttl(&self, key: &K) -> Option<Duration> {
+        let map = self.map.lock();
+        let now = self.live_now();
+        let entry = map.get(key).filter(|entry| entry.expires_at > now)?;
+        Some(remaining_until(entry.expires_at, now))
+    }
+
+    fn snapshot_with_meta(&self) -> Vec<(K, V, Duration)>
+    where
+        V: Clone,
+    {
+        let map = self.map.lock();
+        let now = self.live_now();
+        map.iter()
+            .filter(|(_, entry)| entry.expires_at > now)
+            .map(|(key, entry)| (key.clone(), entry.value.clone(), remaining_until(entry.expires_at, now)))
+            .collect()
+    }
+
+    fn for_each_live_key(&self, f: &mut dyn FnMut(&K)) {
+        let map = self.map.lock();
+        let now = self.live_now();
+        for (key, entry) in map.iter() {
+            if entry.expires_at > now {
+                f(key);
+            }
+        }
+    }
+
+    fn is_healthy(&self) -> bool {
+        self.map.is_healthy()
+            && self.freq_map.is_healthy()
+            && self.expiry.is_healthy()
+            && self.eviction_filter.is_healthy()
+            && self.ttl.is_healthy()
+            && self.policy.is_healthy()
+            && self.frozen_at.is_healthy()
+            && self.cleaners.is_healthy()
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        let map = self.map.lock();
+        let freq_map = self.freq_map.lock();
+        let limit = self.options.eviction_timing.write_limit(self.capacity);
+        let pinned = map.values().filter(|entry| entry.pinned).count();
+        if !self.is_frozen() && map.len() > limit.saturating_add(pinned) {
+            return Err(format!("len {} exceeds capacity limit {} plus {} pinned", map.len(), limit, pinned));
+        }
+        let tracked: usize = freq_map.values().map(|bucket| bucket.len()).sum();
+        if tracked != map.len() {
+            return Err(format!("freq_map tracks {} keys but map holds {}", tracked, map.len()));
+        }
+        for (&frequency, bucket) in freq_map.iter() {
+            if bucket.is_empty() {
+                return Err(format!("empty bucket for frequency {}", frequency));
+            }
+            for (&seq, key) in bucket.iter() {
+                match map.get(key) {
+                    Some(entry) if entry.frequency == frequency && entry.seq == seq => {}
+                    Some(_) => return Err(format!("bucket {} disagrees with its entry", frequency)),
+                    None => return Err("freq_map references a key missing from the map".to_string()),
+                }
+            }
+        }
+        let expiry = self.expiry.lock();
+        for (key, entry) in map.iter() {
+            if entry.expires_at != DateTime::<Utc>::MAX_UTC && !expiry.contains(entry.expires_at, key) {
+                return Err("entry missing from the expiry index at its deadline".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats.snapshot()
+    }
+
+    fn recent_evictions(&self) -> Vec<(K, DateTime<Utc>, EvictReason)> {
+        self.evictions.snapshot()
     }
 
     fn start_cleaner(&self, clean_interval: Duration) {
-        let map = Arc::clone(&self.map);
+        let sweeper = self.sweeper();
         let notify = Arc::clone(&self.notify_stop);
 
-        task::spawn(async move {
+        let handle = task::spawn(async move {
             loop {
                 tokio::select! {
-                    _ = sleep(clean_interval) => {
-                        let now = Utc::now();
-                        let mut map = map.lock().unwrap();
-
-                        map.retain(|_key, entry| entry.expires_at > now);
-                    }
+                    _ = sleep(clean_interval) => sweeper.tick().await,
                     _ = notify.notified() => {
                         break;
                     }
                 }
             }
         });
+        self.cleaners.lock().push(handle);
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<CacheEvent<K>> {
+        self.events.subscribe()
     }
 
     fn stop_cleaner(&self) {
         self.notify_stop.notify_waiters();
     }
+
+    fn take_cleaners(&self) -> Vec<JoinHandle<()>> {
+        std::mem::take(&mut *self.cleaners.lock())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
 }
@@ -2,22 +2,688 @@ pub mod fifo;
 pub mod lfu;
 pub mod lru;
 
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hash};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::sync::Mutex;
+
+/// Consulted before a capacity eviction; returning `false` keeps the entry
+/// and moves on to the next candidate.
+pub type EvictionFilter<K, V> = Arc<dyn Fn(&K, &V) -> bool + Send + Sync>;
+
+/// Implementations lock the map before their ordering structure (`order` or
+/// `freq_map`), and that before the expiry index. An operation touching
+/// several of them, `clear` included, holds all of its locks at once, so no
+/// observer sees a half-applied change.
 pub trait CacheStrategy<K, V>: Send + Sync {
-    fn put(&mut self, key: K, value: V);
-    fn get(&mut self, key: &K) -> Option<V>;
-    fn remove(&mut self, key: &K);
+    fn put(&self, key: K, value: V);
+    /// Like `put_evicting`, but the entry never expires on TTL grounds.
+    fn put_permanent(&self, key: K, value: V) -> Result<Vec<(K, V)>, (K, V)>;
+    /// Inserts like `put` and returns every entry evicted to make room;
+    /// more than one when watermarks evict a batch. Hands the pair back if
+    /// the put was dropped: by a zero-capacity cache, or by FIFO keeping an
+    /// existing key without a `fifo_replace` mode.
+    fn put_evicting(&self, key: K, value: V) -> Result<Vec<(K, V)>, (K, V)>;
+    /// Like `put_evicting`, but the entry expires after `ttl`.
+    fn put_with_ttl(&self, key: K, value: V, ttl: Duration) -> Result<Vec<(K, V)>, (K, V)>;
+    fn try_put(&self, key: K, value: V) -> Result<(), (K, V)>;
+    /// Inserts every entry with the default TTL under a single lock
+    /// acquisition, evicting as `put` would.
+    fn put_many(&self, entries: &mut dyn Iterator<Item = (K, V)>) {
+        let expires_at = deadline_after(self.now(), self.ttl());
+        self.restore(&mut entries.map(|(key, value)| (key, value, expires_at)));
+    }
+    /// Like `put_many`, but each entry keeps the given deadline. Entries
+    /// already past it are skipped.
+    fn restore(&self, entries: &mut dyn Iterator<Item = (K, V, DateTime<Utc>)>);
+    fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone;
+    /// Calls `f` with the live value for `key`, counted as a hit like `get`
+    /// but without cloning. `f` runs under the cache's locks and is not
+    /// called on a miss.
+    fn get_with(&self, key: &K, f: &mut dyn FnMut(&V));
+    /// Returns the live value, or stores `default` and returns it together
+    /// with the entries evicted to make room.
+    fn get_or_insert(&self, key: K, default: V) -> (V, Vec<(K, V)>)
+    where
+        V: Clone;
+    /// Inserts with `ttl` only if `key` is absent or expired and returns the
+    /// entries evicted to make room, or `None` if nothing was inserted. The
+    /// check is not counted as an access.
+    fn put_if_absent_with_ttl(&self, key: K, value: V, ttl: Duration) -> Option<Vec<(K, V)>>;
+    fn get_detailed(&self, key: &K) -> GetResult<V>
+    where
+        V: Clone;
+    fn get_if_fresh_within(&self, key: &K, max_age: Duration) -> Option<V>
+    where
+        V: Clone;
+    fn get_key_value(&self, key: &K) -> Option<(K, V)>
+    where
+        V: Clone;
+    /// Like `get`, counting as an access, but with the entry's metadata.
+    fn get_with_metadata(&self, key: &K) -> Option<CacheItem<V>>
+    where
+        V: Clone;
+    fn get_consistent(&self, keys: &[K]) -> Vec<Option<V>>
+    where
+        V: Clone;
+    /// Like `get_consistent`, with `promote` deciding which hits count
+    /// toward recency (LRU) or frequency (LFU). FIFO ignores it.
+    fn get_group(&self, keys: &[K], promote: PromotePolicy) -> Vec<Option<V>>
+    where
+        V: Clone;
+    fn remove(&self, key: &K);
+    fn take(&self, key: &K) -> Option<V>;
+    fn remove_many(&self, keys: &[K]) -> Vec<Option<V>>;
+    fn extend_ttl(&self, key: &K, by: Duration) -> bool;
+    fn touch_matching(&self, pred: &dyn Fn(&K) -> bool) -> usize;
+    /// Resets a live entry's deadline to `now + ttl` and counts it as an
+    /// access without cloning the value. Permanent entries keep no deadline.
+    fn contains_and_refresh(&self, key: &K) -> bool;
+    fn update_many(&self, keys: &[K], f: &mut dyn FnMut(&K, &mut V));
+    fn prune_to(&self, target_len: usize) -> usize;
+    fn pin(&self, key: &K) -> bool;
+    fn unpin(&self, key: &K);
+    fn peek_victim(&self) -> Option<K>;
+    fn debug_order(&self) -> Vec<K>;
     fn contains(&self, key: &K) -> bool;
     fn len(&self) -> usize;
+    /// Entries still stored although their deadline has passed, i.e. left
+    /// for the cleaner or the next access to drop.
+    fn expired_but_present_count(&self) -> usize;
     fn is_empty(&self) -> bool;
-    fn clear(&mut self);
+    fn clear(&self);
+    /// Clears only if every lock can be taken without waiting; returns
+    /// whether it did.
+    fn try_clear(&self) -> bool;
+    fn drain(&self) -> Vec<(K, V)>;
+    /// Like `drain`, but in eviction order, next victim first, and with each
+    /// entry's deadline.
+    fn drain_entries(&self) -> Vec<(K, V, DateTime<Utc>)>;
+    fn drain_expired(&self) -> Vec<(K, V)>;
+    /// Runs one cleaner sweep: drops expired entries (and trims to capacity
+    /// under lazy eviction) and returns how many expired.
+    fn evict_expired(&self) -> usize;
+    /// Runs one background cleaner tick in place, honouring the configured
+    /// batch size and time budget.
+    fn step_cleaner(&self);
+    /// While frozen nothing leaves the cache on its own: capacity eviction
+    /// and expiry sweeps are skipped and lookups judge expiry at the instant
+    /// of freezing.
+    /// Unfreezing evicts back down to capacity.
+    fn set_frozen(&self, frozen: bool);
+    fn is_frozen(&self) -> bool;
+    fn capacity(&self) -> usize;
+    fn ttl(&self) -> Duration;
+    /// The current time according to this cache's `Clock`.
+    fn now(&self) -> DateTime<Utc>;
+    /// Changes the default TTL for later inserts and sliding refreshes;
+    /// deadlines already computed are kept.
+    fn set_ttl(&self, ttl: Duration);
+    fn set_expiration_policy(&self, policy: ExpirationPolicy);
+    /// Installs or removes the filter consulted before each capacity
+    /// eviction. When it vetoes every unpinned entry, the strategy's own
+    /// victim is evicted anyway so the cache stays within capacity.
+    fn set_eviction_filter(&self, filter: Option<EvictionFilter<K, V>>);
+    fn expiration_policy(&self) -> ExpirationPolicy;
+    fn reserve(&self, additional: usize);
+    fn shrink_to_fit(&self);
+    fn compact(&self);
+    fn allocated_capacity(&self) -> usize;
+    /// Estimated bytes held by the cache's own structures: the entry map,
+    /// the eviction order or frequency buckets, and the expiry index. Heap
+    /// data owned by keys and values, such as a `String`'s buffer, is not
+    /// counted.
+    fn memory_footprint(&self) -> usize;
+    fn remaining_ttl(&self, key: &K) -> Option<Duration>;
+    fn snapshot_with_meta(&self) -> Vec<(K, V, Duration)>
+    where
+        V: Clone;
+    fn for_each_live_key(&self, f: &mut dyn FnMut(&K));
+    fn check_invariants(&self) -> Result<(), String>;
+    /// `false` if any internal lock is poisoned or stays held across a few
+    /// brief retries.
+    fn is_healthy(&self) -> bool;
+    fn stats(&self) -> CacheStats {
+        CacheStats::default()
+    }
+    /// The last `RECENT_EVICTIONS_CAPACITY` capacity evictions and
+    /// expirations, oldest first.
+    fn recent_evictions(&self) -> Vec<(K, DateTime<Utc>, EvictReason)> {
+        Vec::new()
+    }
+    fn subscribe(&self) -> broadcast::Receiver<CacheEvent<K>>;
     fn start_cleaner(&self, interval: Duration);
     fn stop_cleaner(&self);
+    fn take_cleaners(&self) -> Vec<JoinHandle<()>>;
+    /// The concrete strategy, for reaching methods outside this trait.
+    fn as_any(&self) -> &dyn Any;
+}
+
+pub(crate) fn written_within(inserted_at: DateTime<Utc>, now: DateTime<Utc>, max_age: Option<Duration>) -> bool {
+    match max_age {
+        None => true,
+        Some(max_age) => chrono::Duration::from_std(max_age).is_ok_and(|age| now - inserted_at <= age),
+    }
+}
+
+/// `now + ttl`, saturating to "never expires" instead of overflowing.
+pub(crate) fn deadline_after(now: DateTime<Utc>, ttl: Duration) -> DateTime<Utc> {
+    chrono::Duration::from_std(ttl)
+        .ok()
+        .and_then(|ttl| now.checked_add_signed(ttl))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// Time left until `expires_at`, with `Duration::MAX` standing for a
+/// permanent entry.
+pub(crate) fn remaining_until(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> Duration {
+    if expires_at == DateTime::<Utc>::MAX_UTC {
+        return Duration::MAX;
+    }
+    (expires_at - now).to_std().unwrap_or(Duration::ZERO)
+}
+
+/// Pushes `expires_at` back by `by`, saturating to "never expires".
+pub(crate) fn extend_deadline(expires_at: &mut DateTime<Utc>, by: Duration) {
+    *expires_at = chrono::Duration::from_std(by)
+        .ok()
+        .and_then(|by| expires_at.checked_add_signed(by))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+}
+
+/// Keys grouped by deadline, so the cleaner can pop expired entries in
+/// deadline order and stop at the first future bucket instead of scanning
+/// the whole map. Every deadline change and every removal must be mirrored
+/// here, so the index never holds more slots than the map holds entries.
+/// Permanent entries are never indexed. Buckets hash with
+/// the cache's `HashState`, so a deterministic cache also expires keys
+/// sharing a deadline in a reproducible order.
+pub(crate) struct ExpiryIndex<K> {
+    buckets: BTreeMap<DateTime<Utc>, HashSet<K, HashState>>,
+    hash_state: HashState,
+}
+
+impl<K: Eq + Hash + Clone> ExpiryIndex<K> {
+    pub(crate) fn new(hash_state: HashState) -> Self {
+        ExpiryIndex { buckets: BTreeMap::new(), hash_state }
+    }
+
+    pub(crate) fn schedule(&mut self, deadline: DateTime<Utc>, key: K) {
+        if deadline != DateTime::<Utc>::MAX_UTC {
+            self.buckets
+                .entry(deadline)
+                .or_insert_with(|| HashSet::with_hasher(self.hash_state.clone()))
+                .insert(key);
+        }
+    }
+
+    pub(crate) fn reschedule(&mut self, old: DateTime<Utc>, new: DateTime<Utc>, key: &K) {
+        if old == new {
+            return;
+        }
+        self.unschedule(old, key);
+        self.schedule(new, key.clone());
+    }
+
+    pub(crate) fn unschedule(&mut self, deadline: DateTime<Utc>, key: &K) {
+        if let Some(bucket) = self.buckets.get_mut(&deadline) {
+            bucket.remove(key);
+            if bucket.is_empty() {
+                self.buckets.remove(&deadline);
+            }
+        }
+    }
+
+    /// Removes and returns one slot whose deadline is at or before `now`.
+    pub(crate) fn pop_due(&mut self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, K)> {
+        let mut bucket = self.buckets.first_entry()?;
+        let deadline = *bucket.key();
+        if deadline > now {
+            return None;
+        }
+        let key = bucket.get().iter().next().cloned()?;
+        bucket.get_mut().remove(&key);
+        if bucket.get().is_empty() {
+            bucket.remove();
+        }
+        Some((deadline, key))
+    }
+
+    pub(crate) fn contains(&self, deadline: DateTime<Utc>, key: &K) -> bool {
+        self.buckets.get(&deadline).is_some_and(|bucket| bucket.contains(key))
+    }
+
+    pub(crate) fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.buckets.clear();
+    }
+
+    pub(crate) fn footprint(&self) -> usize {
+        self.buckets.values()
+            .map(|keys| btree_entry_footprint::<DateTime<Utc>, HashSet<K, HashState>>() + keys.capacity() * (size_of::<K>() + 1))
+            .sum()
+    }
+
+    /// Replaces the index with one holding exactly `entries`.
+    pub(crate) fn rebuild(&mut self, entries: impl Iterator<Item = (DateTime<Utc>, K)>) {
+        self.buckets.clear();
+        for (deadline, key) in entries {
+            self.schedule(deadline, key);
+        }
+    }
+}
+
+pub(crate) const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Rough bytes a `BTreeMap` spends per entry beyond the entry itself: node
+/// headers, edges and slack in partly filled nodes.
+const BTREE_ENTRY_OVERHEAD: usize = 16;
+
+/// Allocated slots plus one control byte each, as in hashbrown.
+pub(crate) fn hash_map_footprint<K, V, S>(map: &HashMap<K, V, S>) -> usize {
+    map.capacity() * (size_of::<(K, V)>() + 1)
+}
+
+pub(crate) fn btree_entry_footprint<K, V>() -> usize {
+    size_of::<(K, V)>() + BTREE_ENTRY_OVERHEAD
+}
+
+/// Batch size a time-budgeted cleaner uses when `cleaner_batch` is unset, so
+/// the budget is checked often enough to matter.
+pub(crate) const BUDGETED_CLEANER_BATCH: usize = 256;
+
+/// A change to the cache contents, broadcast to subscribers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CacheEvent<K> {
+    Inserted(K),
+    /// A live value was modified in place by `update_many`. Not counted as
+    /// an insertion.
+    Updated(K),
+    Evicted(K),
+    Expired(K),
+    Removed(K),
+}
+
+/// Which reads of a `get_group` call count as accesses for eviction order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PromotePolicy {
+    /// Every hit, as if each key were read with `get`.
+    #[default]
+    All,
+    /// Only the last key in the group.
+    Last,
+    /// No key; the eviction order is left as it was.
+    None,
+}
+
+impl PromotePolicy {
+    pub(crate) fn promotes(self, index: usize, len: usize) -> bool {
+        match self {
+            PromotePolicy::All => true,
+            PromotePolicy::Last => index + 1 == len,
+            PromotePolicy::None => false,
+        }
+    }
+}
+
+/// Outcome of a lookup, separating a key that was never cached from one whose
+/// entry had already expired.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GetResult<V> {
+    Hit(V),
+    Expired,
+    Missing,
+}
+
+impl<V> GetResult<V> {
+    pub fn into_option(self) -> Option<V> {
+        match self {
+            GetResult::Hit(value) => Some(value),
+            GetResult::Expired | GetResult::Missing => None,
+        }
+    }
+}
+
+/// A live entry's value together with its bookkeeping, as returned by
+/// `get_with_metadata`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheItem<V> {
+    pub value: V,
+    pub inserted_at: DateTime<Utc>,
+    /// `None` for a permanent entry.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// `Duration::MAX` for a permanent entry.
+    pub remaining_ttl: Duration,
+    /// Access count for LFU, counting this lookup; `None` for the other
+    /// strategies.
+    pub frequency: Option<usize>,
+}
+
+impl<V> CacheItem<V> {
+    pub(crate) fn new(value: V, inserted_at: DateTime<Utc>, expires_at: DateTime<Utc>, frequency: Option<usize>, now: DateTime<Utc>) -> Self {
+        let remaining_ttl = remaining_until(expires_at, now);
+        let expires_at = (expires_at != DateTime::<Utc>::MAX_UTC).then_some(expires_at);
+        CacheItem { value, inserted_at, expires_at, remaining_ttl, frequency }
+    }
+}
+
+/// Counters accumulated since the cache was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    pub evictions: EvictionBreakdown,
+}
+
+/// Entries that left the cache, by cause.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvictionBreakdown {
+    pub capacity: u64,
+    pub expired: u64,
+    pub removed: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct StatsCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    insertions: AtomicU64,
+    capacity: AtomicU64,
+    expired: AtomicU64,
+    removed: AtomicU64,
 }
 
+impl StatsCounters {
+    pub(crate) fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record<K>(&self, event: &CacheEvent<K>) {
+        let counter = match event {
+            CacheEvent::Inserted(_) => &self.insertions,
+            CacheEvent::Evicted(_) => &self.capacity,
+            CacheEvent::Expired(_) => &self.expired,
+            CacheEvent::Removed(_) => &self.removed,
+            CacheEvent::Updated(_) => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            insertions: self.insertions.load(Ordering::Relaxed),
+            evictions: EvictionBreakdown {
+                capacity: self.capacity.load(Ordering::Relaxed),
+                expired: self.expired.load(Ordering::Relaxed),
+                removed: self.removed.load(Ordering::Relaxed),
+            },
+        }
+    }
+}
+
+/// Why an entry appears in `recent_evictions`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvictReason {
+    Capacity,
+    Expired,
+}
+
+/// How many entries `recent_evictions` remembers.
+pub const RECENT_EVICTIONS_CAPACITY: usize = 128;
+
+/// Ring buffer of recent capacity evictions and expirations. Explicit
+/// removals are not recorded.
+pub(crate) struct EvictionLog<K> {
+    entries: Mutex<VecDeque<(K, DateTime<Utc>, EvictReason)>>,
+}
+
+impl<K: Clone> EvictionLog<K> {
+    pub(crate) fn new() -> Self {
+        EvictionLog {
+            entries: Mutex::new(VecDeque::with_capacity(RECENT_EVICTIONS_CAPACITY)),
+        }
+    }
+
+    pub(crate) fn record(&self, event: &CacheEvent<K>, at: DateTime<Utc>) {
+        let (key, reason) = match event {
+            CacheEvent::Evicted(key) => (key, EvictReason::Capacity),
+            CacheEvent::Expired(key) => (key, EvictReason::Expired),
+            CacheEvent::Inserted(_) | CacheEvent::Updated(_) | CacheEvent::Removed(_) => return,
+        };
+        let mut entries = self.entries.lock();
+        if entries.len() == RECENT_EVICTIONS_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back((key.clone(), at, reason));
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<(K, DateTime<Utc>, EvictReason)> {
+        self.entries.lock().iter().cloned().collect()
+    }
+}
+
+/// How a successful read affects an entry's deadline.
+///
+/// `Absolute` (the default) treats the TTL as a hard cap measured from the
+/// last write. `Sliding` pushes the deadline back to `now + ttl` on every hit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExpirationPolicy {
+    #[default]
+    Absolute,
+    Sliding,
+}
+
+impl ExpirationPolicy {
+    pub(crate) fn on_access(&self, expires_at: &mut DateTime<Utc>, now: DateTime<Utc>, ttl: Duration) {
+        if *self == ExpirationPolicy::Sliding && *expires_at != DateTime::<Utc>::MAX_UTC {
+            *expires_at = deadline_after(now, ttl);
+        }
+    }
+}
+
+/// How a FIFO cache treats a put for a key it already holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FifoReplaceMode {
+    /// Replace the value and TTL; the key keeps its place in the queue.
+    KeepPosition,
+    /// Replace the value and TTL and requeue the key as the newest entry.
+    MoveToBack,
+}
+
+/// When capacity eviction happens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EvictionTiming {
+    /// `put` evicts synchronously, so `len` never exceeds the capacity.
+    #[default]
+    Eager,
+    /// `put` only evicts once `len` reaches `capacity + slack`; the cleaner
+    /// trims back down to the capacity on its next sweep.
+    Lazy { slack: usize },
+    /// Once `len` reaches `high`, the next `put` evicts down to `low` in one
+    /// pass before inserting. The capacity setting is ignored.
+    Watermarks { low: usize, high: usize },
+}
+
+impl EvictionTiming {
+    pub(crate) fn write_limit(&self, capacity: usize) -> usize {
+        match self {
+            EvictionTiming::Eager => capacity,
+            EvictionTiming::Lazy { slack } => capacity.saturating_add(*slack),
+            EvictionTiming::Watermarks { high, .. } => *high,
+        }
+    }
+
+    /// How many entries may remain once a full cache has made room for a put.
+    pub(crate) fn evict_target(&self, capacity: usize) -> usize {
+        match self {
+            EvictionTiming::Watermarks { low, .. } => *low,
+            _ => self.write_limit(capacity).saturating_sub(1),
+        }
+    }
+}
+
+/// Tuning knobs shared by every strategy, usually set through
+/// `RustycacheBuilder`.
+#[derive(Clone, Debug, Default)]
+pub struct CacheOptions {
+    pub deterministic: bool,
+    pub eviction_timing: EvictionTiming,
+    /// Policy in effect from construction; `set_expiration_policy` can still
+    /// change it later.
+    pub expiration_policy: ExpirationPolicy,
+    /// Maximum number of expired entries a cleaner sweep removes per lock
+    /// acquisition. The cleaner yields between batches. `None` removes
+    /// everything in one pass.
+    pub cleaner_batch: Option<usize>,
+    /// Wall-clock time a cleaner tick may spend removing expired entries.
+    /// Once spent, the rest of the backlog waits for the next tick. Checked
+    /// between batches, so a tick can overrun by up to one batch.
+    pub cleaner_time_budget: Option<Duration>,
+    /// Replace behaviour for FIFO caches. `None` keeps the default of
+    /// ignoring puts for keys already present.
+    pub fifo_replace: Option<FifoReplaceMode>,
+    /// Maximum number of expired entries each put reclaims before inserting,
+    /// so TTL cleanup still happens without a cleaner task. `None` leaves
+    /// cleanup to the cleaner and explicit purges.
+    pub amortized_cleanup: Option<usize>,
+    /// LRU only: promote a key to most-recently-used on every Nth access
+    /// instead of every access, trading recency precision for less
+    /// reordering. `None` promotes on every access.
+    pub promote_every: Option<usize>,
+    /// Where deadlines and expiry checks read the time from.
+    pub clock: Clock,
+}
+
+/// Time source for a cache's deadlines. `System` reads the wall clock;
+/// `Mock` stands still until advanced, so a simulation decides exactly when
+/// entries expire.
+#[derive(Clone, Debug, Default)]
+pub enum Clock {
+    #[default]
+    System,
+    Mock(MockClock),
+}
+
+impl Clock {
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::System => Utc::now(),
+            Clock::Mock(clock) => clock.now(),
+        }
+    }
+}
+
+impl From<MockClock> for Clock {
+    fn from(clock: MockClock) -> Self {
+        Clock::Mock(clock)
+    }
+}
+
+/// A manually driven clock. Clones share the same time, so the handle kept
+/// by a simulation moves the clock of every cache built with it.
+#[derive(Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        MockClock { now: Arc::new(Mutex::new(start)) }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        *self.now.lock()
+    }
+
+    /// Moves the clock forward by `by`, saturating at the latest
+    /// representable time.
+    pub fn advance_time(&self, by: Duration) {
+        extend_deadline(&mut self.now.lock(), by);
+    }
+}
+
+impl std::fmt::Debug for MockClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockClock").field("now", &self.now()).finish()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StrategyType {
     LRU,
     FIFO,
     LFU,
 }
+
+impl StrategyType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StrategyType::LRU => "LRU",
+            StrategyType::FIFO => "FIFO",
+            StrategyType::LFU => "LFU",
+        }
+    }
+}
+
+#[cfg(feature = "fast-hash")]
+type RandomState = ahash::RandomState;
+#[cfg(feature = "fast-hash")]
+type DefaultHasher = ahash::AHasher;
+#[cfg(not(feature = "fast-hash"))]
+use std::collections::hash_map::{DefaultHasher, RandomState};
+
+/// Hasher state for internal maps: SipHash by default, or aHash with the
+/// `fast-hash` feature. `Fixed` uses constant keys so that iteration order,
+/// and therefore eviction order, is reproducible.
+#[derive(Clone)]
+pub(crate) enum HashState {
+    Random(RandomState),
+    Fixed,
+}
+
+impl HashState {
+    pub(crate) fn new(deterministic: bool) -> Self {
+        if deterministic {
+            HashState::Fixed
+        } else {
+            HashState::Random(RandomState::new())
+        }
+    }
+}
+
+impl BuildHasher for HashState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        match self {
+            HashState::Random(state) => state.build_hasher(),
+            #[cfg(feature = "fast-hash")]
+            HashState::Fixed => RandomState::with_seeds(0, 0, 0, 0).build_hasher(),
+            #[cfg(not(feature = "fast-hash"))]
+            HashState::Fixed => DefaultHasher::new(),
+        }
+    }
+}
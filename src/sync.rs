@@ -0,0 +1,78 @@
+//! Lock wrapper shared by the strategies. With the `parking-lot` feature the
+//! internal maps are guarded by `parking_lot::Mutex`; otherwise by
+//! `std::sync::Mutex`, where a poisoned lock panics on acquisition.
+
+use std::time::Duration;
+
+/// How many times `is_healthy` retries a held lock before calling it wedged,
+/// sleeping `HEALTH_PROBE_BACKOFF` in between.
+const HEALTH_PROBES: u32 = 10;
+const HEALTH_PROBE_BACKOFF: Duration = Duration::from_millis(1);
+
+#[cfg(feature = "parking-lot")]
+pub(crate) type MutexGuard<'a, T> = parking_lot::MutexGuard<'a, T>;
+#[cfg(not(feature = "parking-lot"))]
+pub(crate) type MutexGuard<'a, T> = std::sync::MutexGuard<'a, T>;
+
+pub(crate) struct Mutex<T> {
+    #[cfg(feature = "parking-lot")]
+    inner: parking_lot::Mutex<T>,
+    #[cfg(not(feature = "parking-lot"))]
+    inner: std::sync::Mutex<T>,
+}
+
+#[cfg(feature = "parking-lot")]
+impl<T> Mutex<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Mutex { inner: parking_lot::Mutex::new(value) }
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        self.inner.lock()
+    }
+
+    pub(crate) fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.inner.try_lock()
+    }
+
+    /// `false` if the lock is still held after a few brief retries.
+    /// `parking_lot` locks are never poisoned.
+    pub(crate) fn is_healthy(&self) -> bool {
+        for _ in 0..HEALTH_PROBES {
+            if self.inner.try_lock().is_some() {
+                return true;
+            }
+            std::thread::sleep(HEALTH_PROBE_BACKOFF);
+        }
+        false
+    }
+}
+
+#[cfg(not(feature = "parking-lot"))]
+impl<T> Mutex<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Mutex { inner: std::sync::Mutex::new(value) }
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        self.inner.lock().unwrap()
+    }
+
+    /// `None` if the lock is held elsewhere or poisoned.
+    pub(crate) fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.inner.try_lock().ok()
+    }
+
+    /// `false` if the lock is poisoned or still held after a few brief
+    /// retries.
+    pub(crate) fn is_healthy(&self) -> bool {
+        for _ in 0..HEALTH_PROBES {
+            match self.inner.try_lock() {
+                Ok(_) => return true,
+                Err(std::sync::TryLockError::Poisoned(_)) => return false,
+                Err(std::sync::TryLockError::WouldBlock) => std::thread::sleep(HEALTH_PROBE_BACKOFF),
+            }
+        }
+        false
+    }
+}
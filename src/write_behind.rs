@@ -0,0 +1,115 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::task;
+use tokio::time::interval;
+
+/// Shortest flush period. `interval` panics on zero, which would kill the
+/// task and silently drop every later write.
+const MIN_FLUSH_INTERVAL: Duration = Duration::from_millis(1);
+
+type Sink<K, V> = Arc<dyn Fn(K, V) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+enum Message<K, V> {
+    Write(K, V),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Queue of writes forwarded to a backing store by a background task. Writes
+/// are buffered and handed to the sink once `batch_size` are pending, on every
+/// `flush_interval` tick, or when `flush` is called.
+pub(crate) struct WriteBehind<K, V> {
+    tx: mpsc::UnboundedSender<Message<K, V>>,
+    /// Captured at `spawn` so that callers forwarding a borrowed value need
+    /// no `Clone` bound of their own.
+    clone_value: fn(&V) -> V,
+}
+
+impl<K, V> WriteBehind<K, V>
+where
+    K: Send + 'static,
+    V: Send + 'static,
+{
+    pub(crate) fn spawn<F, Fut>(sink: F, batch_size: usize, flush_interval: Duration) -> Self
+    where
+        F: Fn(K, V) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        V: Clone,
+    {
+        let sink: Sink<K, V> = Arc::new(move |key, value| Box::pin(sink(key, value)));
+        let batch_size = batch_size.max(1);
+        let flush_interval = flush_interval.max(MIN_FLUSH_INTERVAL);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        task::spawn(async move {
+            let mut pending = Vec::with_capacity(batch_size);
+            let mut ticker = interval(flush_interval);
+            loop {
+                tokio::select! {
+                    message = rx.recv() => match message {
+                        Some(Message::Write(key, value)) => {
+                            pending.push((key, value));
+                            if pending.len() >= batch_size {
+                                drain(&sink, &mut pending).await;
+                            }
+                        }
+                        Some(Message::Flush(done)) => {
+                            drain(&sink, &mut pending).await;
+                            let _ = done.send(());
+                        }
+                        None => {
+                            drain(&sink, &mut pending).await;
+                            break;
+                        }
+                    },
+                    _ = ticker.tick() => drain(&sink, &mut pending).await,
+                }
+            }
+        });
+
+        WriteBehind { tx, clone_value: V::clone }
+    }
+
+    fn enqueue(&self, key: K, value: V) {
+        let _ = self.tx.send(Message::Write(key, value));
+    }
+
+    /// Copies a write before its value moves into the cache. Nothing reaches
+    /// the sink unless the copy is confirmed.
+    pub(crate) fn pending(&self, key: &K, value: &V) -> PendingWrite<'_, K, V>
+    where
+        K: Clone,
+    {
+        PendingWrite { write_behind: self, key: key.clone(), value: (self.clone_value)(value) }
+    }
+
+    pub(crate) async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.tx.send(Message::Flush(done)).is_ok() {
+            let _ = wait.await;
+        }
+    }
+}
+
+/// A write held back until the cache reports it stored, so the backing
+/// store never receives a value the cache dropped.
+pub(crate) struct PendingWrite<'a, K, V> {
+    write_behind: &'a WriteBehind<K, V>,
+    key: K,
+    value: V,
+}
+
+impl<K: Send + 'static, V: Send + 'static> PendingWrite<'_, K, V> {
+    pub(crate) fn confirm(self) {
+        self.write_behind.enqueue(self.key, self.value);
+    }
+}
+
+async fn drain<K, V>(sink: &Sink<K, V>, pending: &mut Vec<(K, V)>) {
+    for (key, value) in pending.drain(..) {
+        sink(key, value).await;
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod cost_aware_tests {
+    use std::time::Duration;
+    use rustycache::cost_aware::CostAwareCache;
+
+    #[test]
+    fn test_high_cost_entry_outlives_cheap_frequent_one() {
+        let cache = CostAwareCache::new(2, Duration::from_secs(30));
+        cache.put_with_cost("expensive", 1, 100.0);
+        cache.put_with_cost("cheap", 2, 1.0);
+        for _ in 0..10 {
+            assert_eq!(cache.get(&"cheap"), Some(2));
+        }
+        assert_eq!(cache.get(&"expensive"), Some(1));
+
+        assert_eq!(cache.put_with_cost("new", 3, 1.0), Some(("cheap", 2)));
+        assert!(cache.contains(&"expensive"));
+        assert!(!cache.contains(&"cheap"));
+    }
+
+    #[test]
+    fn test_evictions_age_out_untouched_entries() {
+        let cache = CostAwareCache::new(2, Duration::from_secs(30));
+        cache.put_with_cost("stale".to_string(), 0, 5.0);
+        for i in 1..=10 {
+            cache.put_with_cost(i.to_string(), i, 1.0);
+        }
+        assert!(!cache.contains(&"stale".to_string()));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_custom_scorer_decides_the_victim() {
+        // Prefer keeping cheap entries instead.
+        let cache = CostAwareCache::with_scorer(2, Duration::from_secs(30), |_, cost| 1.0 / cost);
+        cache.put_with_cost("expensive", 1, 100.0);
+        cache.put_with_cost("cheap", 2, 1.0);
+        assert_eq!(cache.peek_victim(), Some("expensive"));
+        assert_eq!(cache.put("other", 3), Some(("expensive", 1)));
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped() {
+        let cache = CostAwareCache::new(4, Duration::from_millis(20));
+        cache.put("a", 1);
+        cache.put("b", 2);
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(cache.get(&"a"), None);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.purge_expired(), 1);
+        assert!(cache.is_empty());
+    }
+}
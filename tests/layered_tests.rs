@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod layered_tests {
+    use std::time::Duration;
+    use rustycache::layered::{LayeredCache, WritePolicy};
+    use rustycache::rustycache::Rustycache;
+    use rustycache::strategy::StrategyType;
+
+    fn create_cache(l1_capacity: usize, l2_capacity: usize) -> LayeredCache<String, String> {
+        let l1 = Rustycache::new(l1_capacity, Duration::from_secs(5), Duration::from_secs(60), StrategyType::LRU);
+        let l2 = Rustycache::new(l2_capacity, Duration::from_secs(5), Duration::from_secs(60), StrategyType::LRU);
+        LayeredCache::new(l1, l2)
+    }
+
+    #[tokio::test]
+    async fn test_l2_hit_is_promoted_to_l1() {
+        let mut cache = create_cache(2, 10).with_write_policy(WritePolicy::L2Only);
+        cache.put("a".to_string(), "A".to_string());
+        assert!(!cache.l1().contains(&"a".to_string()));
+
+        assert_eq!(cache.get(&"a".to_string()), Some("A".to_string()));
+        assert!(cache.l1().contains(&"a".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_l2_only_overwrite_invalidates_l1() {
+        let mut cache = create_cache(2, 10).with_write_policy(WritePolicy::L2Only);
+        cache.put("a".to_string(), "A".to_string());
+        cache.get(&"a".to_string());
+        cache.put("a".to_string(), "B".to_string());
+
+        assert!(!cache.l1().contains(&"a".to_string()));
+        assert_eq!(cache.get(&"a".to_string()), Some("B".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_l1_eviction_keeps_data_in_l2() {
+        let mut cache = create_cache(2, 10);
+        for key in ["a", "b", "c"] {
+            cache.put(key.to_string(), key.to_uppercase());
+        }
+
+        assert!(!cache.l1().contains(&"a".to_string()));
+        assert_eq!(cache.get(&"a".to_string()), Some("A".to_string()));
+        assert!(cache.l1().contains(&"a".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_demotion_moves_l1_victim_to_l2() {
+        let mut cache = create_cache(1, 1)
+            .with_write_policy(WritePolicy::L2Only)
+            .with_demotion(true);
+        cache.put("b".to_string(), "B".to_string());
+        cache.get(&"b".to_string()); // L1 = {b}
+        cache.put("c".to_string(), "C".to_string()); // L2 evicts "b"
+        assert!(!cache.l2().contains(&"b".to_string()));
+
+        cache.get(&"c".to_string()); // promoting "c" pushes "b" out of L1
+
+        assert!(cache.l1().contains(&"c".to_string()));
+        assert!(cache.l2().contains(&"b".to_string()));
+        assert_eq!(cache.get(&"b".to_string()), Some("B".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_remove_clears_both_layers() {
+        let mut cache = create_cache(2, 10);
+        cache.put("a".to_string(), "A".to_string());
+        cache.remove(&"a".to_string());
+
+        assert!(!cache.contains(&"a".to_string()));
+        assert_eq!(cache.get(&"a".to_string()), None);
+    }
+}
@@ -2,7 +2,9 @@
 mod lfu_tests {
     use std::time::Duration;
     use tokio::time::sleep;
-    use rustycache::rustycache::Rustycache;
+    use rustycache::rustycache::{Rustycache, RustycacheBuilder};
+
+    const STRATEGY: rustycache::strategy::StrategyType = rustycache::strategy::StrategyType::LFU;
 
     fn create_cache(capacity: usize, ttl_secs: u64, interval_secs: u64) -> Rustycache<String, String> {
         Rustycache::new(capacity, Duration::from_secs(ttl_secs), Duration::from_secs(interval_secs), rustycache::strategy::StrategyType::LFU)
@@ -11,7 +13,7 @@ mod lfu_tests {
     #[tokio::test]
     async fn test_put_and_get_basic() {
         let mut cache = create_cache(2, 5, 60);
-        cache.put("key1".to_string(), "value1".to_string());
+        cache.insert("key1".to_string(), "value1".to_string());
         assert_eq!(cache.get(&"key1".to_string()), Some("value1".to_string()));
         assert!(cache.contains(&"key1".to_string()));
         assert_eq!(cache.len(), 1);
@@ -20,22 +22,22 @@ mod lfu_tests {
     #[tokio::test]
     async fn test_update_value_and_frequency() {
         let mut cache = create_cache(2, 5, 60);
-        cache.put("key1".to_string(), "value1".to_string());
+        cache.insert("key1".to_string(), "value1".to_string());
         assert_eq!(cache.get(&"key1".to_string()), Some("value1".to_string()));
 
-        cache.put("key1".to_string(), "value2".to_string()); // update value resets frequency?
+        cache.insert("key1".to_string(), "value2".to_string()); // update value resets frequency?
         assert_eq!(cache.get(&"key1".to_string()), Some("value2".to_string()));
     }
 
     #[tokio::test]
     async fn test_lfu_eviction() {
         let mut cache = create_cache(2, 5, 60);
-        cache.put("a".to_string(), "A".to_string());
-        cache.put("b".to_string(), "B".to_string());
+        cache.insert("a".to_string(), "A".to_string());
+        cache.insert("b".to_string(), "B".to_string());
         cache.get(&"a".to_string()); // freq a = 2
         // b freq = 1, a freq = 2
 
-        cache.put("c".to_string(), "C".to_string()); // should evict 'b' (lowest freq)
+        cache.insert("c".to_string(), "C".to_string()); // should evict 'b' (lowest freq)
 
         assert!(cache.get(&"a".to_string()).is_some());
         assert!(cache.get(&"b".to_string()).is_none());
@@ -45,7 +47,7 @@ mod lfu_tests {
     #[tokio::test]
     async fn test_expiration_behavior() {
         let mut cache = create_cache(2, 1, 60);
-        cache.put("x".to_string(), "expire_me".to_string());
+        cache.insert("x".to_string(), "expire_me".to_string());
 
         assert_eq!(cache.get(&"x".to_string()), Some("expire_me".to_string()));
         sleep(Duration::from_secs(2)).await;
@@ -57,9 +59,9 @@ mod lfu_tests {
     #[tokio::test]
     async fn test_remove_and_clear() {
         let mut cache = create_cache(3, 5, 60);
-        cache.put("a".to_string(), "1".to_string());
-        cache.put("b".to_string(), "2".to_string());
-        cache.put("c".to_string(), "3".to_string());
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
 
         cache.remove(&"b".to_string());
         assert_eq!(cache.get(&"b".to_string()), None);
@@ -73,7 +75,7 @@ mod lfu_tests {
     #[tokio::test]
     async fn test_cleaner_removes_expired() {
         let mut cache = create_cache(2, 1, 1); // TTL = 1s, cleaner every 1s
-        cache.put("k1".to_string(), "v1".to_string());
+        cache.insert("k1".to_string(), "v1".to_string());
 
         sleep(Duration::from_secs(2)).await; // wait expiration + cleaner run
 
@@ -96,4 +98,1359 @@ mod lfu_tests {
         cache.start_cleaner(Duration::from_secs(1)); // just ensure no panic
         tokio::time::sleep(Duration::from_millis(100)).await;
     }
+
+    #[tokio::test]
+    async fn test_snapshot_with_meta_reports_remaining_ttl() {
+        let mut cache = create_cache(3, 10, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+
+        let mut snapshot = cache.snapshot_with_meta();
+        snapshot.sort_by(|x, y| x.0.cmp(&y.0));
+
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].0, "a");
+        assert_eq!(snapshot[0].1, "1");
+        for (_, _, remaining) in &snapshot {
+            assert!(*remaining <= Duration::from_secs(10));
+            assert!(*remaining > Duration::from_secs(9));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_permanent_survives_ttl() {
+        let mut cache = create_cache(3, 1, 1);
+        cache.put_permanent("pinned".to_string(), "forever".to_string());
+        cache.insert("short".to_string(), "lived".to_string());
+
+        sleep(Duration::from_secs(3)).await;
+
+        assert_eq!(cache.get(&"pinned".to_string()), Some("forever".to_string()));
+        assert_eq!(cache.get(&"short".to_string()), None);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shrink_to_fit_after_clear() {
+        let mut cache = create_cache(1000, 5, 60);
+        for i in 0..1000 {
+            cache.insert(i.to_string(), i.to_string());
+        }
+        let filled = cache.allocated_capacity();
+        assert!(filled >= 1000);
+
+        cache.clear();
+        cache.shrink_to_fit();
+
+        assert!(cache.allocated_capacity() < filled);
+    }
+
+    #[tokio::test]
+    async fn test_take_removes_and_returns() {
+        let mut cache = create_cache(2, 5, 60);
+        cache.insert("a".to_string(), "A".to_string());
+
+        assert_eq!(cache.take(&"a".to_string()), Some("A".to_string()));
+        assert!(!cache.contains(&"a".to_string()));
+        assert_eq!(cache.take(&"a".to_string()), None);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_take_expired_returns_none() {
+        let mut cache = create_cache(2, 1, 60);
+        cache.insert("a".to_string(), "A".to_string());
+
+        sleep(Duration::from_secs(2)).await;
+
+        assert_eq!(cache.take(&"a".to_string()), None);
+        assert!(!cache.contains(&"a".to_string()));
+    }
+
+    fn eviction_sequence() -> Vec<String> {
+        let mut cache: Rustycache<String, String> = Rustycache::new_deterministic(
+            4,
+            Duration::from_secs(5),
+            Duration::from_secs(60),
+            rustycache::strategy::StrategyType::LFU,
+        );
+        let mut evicted = Vec::new();
+        for i in 0..32 {
+            let before: Vec<String> = cache.snapshot_with_meta().into_iter().map(|(k, _, _)| k).collect();
+            cache.insert(format!("k{}", i), i.to_string());
+            if i % 3 == 0 {
+                cache.get(&format!("k{}", i));
+            }
+            for key in before {
+                if !cache.contains(&key) {
+                    evicted.push(key);
+                }
+            }
+        }
+        evicted
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_eviction_is_reproducible() {
+        let first = eviction_sequence();
+        let second = eviction_sequence();
+
+        assert_eq!(first.len(), 28);
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_receives_events_in_order() {
+        use rustycache::strategy::CacheEvent;
+
+        let mut cache = create_cache(2, 1, 1);
+        let mut events = cache.subscribe();
+
+        cache.insert("a".to_string(), "A".to_string());
+        cache.insert("b".to_string(), "B".to_string());
+        cache.get(&"a".to_string());
+        cache.insert("c".to_string(), "C".to_string()); // evicts "b"
+        cache.remove(&"a".to_string());
+        sleep(Duration::from_secs(3)).await; // cleaner expires "c"
+
+        let expected = vec![
+            CacheEvent::Inserted("a".to_string()),
+            CacheEvent::Inserted("b".to_string()),
+            CacheEvent::Evicted("b".to_string()),
+            CacheEvent::Inserted("c".to_string()),
+            CacheEvent::Removed("a".to_string()),
+            CacheEvent::Expired("c".to_string()),
+        ];
+        for event in expected {
+            assert_eq!(events.recv().await.unwrap(), event);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prune_to_evicts_least_frequent() {
+        let mut cache = create_cache(4, 5, 60);
+        for key in ["a", "b", "c", "d"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        cache.get(&"c".to_string());
+        cache.get(&"d".to_string());
+        cache.get(&"d".to_string());
+
+        assert_eq!(cache.prune_to(2), 2);
+        assert!(cache.contains(&"c".to_string()));
+        assert!(cache.contains(&"d".to_string()));
+
+        assert_eq!(cache.prune_to(1), 1);
+        assert!(cache.contains(&"d".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_reserve_avoids_rehash_during_bulk_load() {
+        let mut cache = create_cache(1000, 5, 60);
+        cache.reserve(1000);
+        let reserved = cache.allocated_capacity();
+        assert!(reserved >= 1000);
+
+        for i in 0..1000 {
+            cache.insert(i.to_string(), i.to_string());
+        }
+
+        assert_eq!(cache.allocated_capacity(), reserved);
+        assert_eq!(cache.len(), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_get_if_fresh_within() {
+        let mut cache = create_cache(3, 10, 60);
+        cache.insert("old".to_string(), "1".to_string());
+        sleep(Duration::from_millis(1200)).await;
+        cache.insert("new".to_string(), "2".to_string());
+
+        let max_age = Duration::from_secs(1);
+        assert_eq!(cache.get_if_fresh_within(&"new".to_string(), max_age), Some("2".to_string()));
+        assert_eq!(cache.get_if_fresh_within(&"old".to_string(), max_age), None);
+        assert_eq!(cache.get(&"old".to_string()), Some("1".to_string())); // still unexpired
+    }
+
+    #[tokio::test]
+    async fn test_drain_expired_returns_expired_entries() {
+        let mut cache = create_cache(4, 1, 60);
+        cache.insert("a".to_string(), "A".to_string());
+        cache.insert("b".to_string(), "B".to_string());
+        cache.put_permanent("live".to_string(), "L".to_string());
+
+        sleep(Duration::from_secs(2)).await;
+
+        let mut drained = cache.drain_expired();
+        drained.sort();
+        assert_eq!(drained, vec![
+            ("a".to_string(), "A".to_string()),
+            ("b".to_string(), "B".to_string()),
+        ]);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&"live".to_string()));
+        assert!(cache.drain_expired().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_eager_eviction_never_exceeds_capacity() {
+        use rustycache::strategy::EvictionTiming;
+
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(3)
+            .ttl(Duration::from_secs(5))
+            .clean_interval(Duration::from_secs(60))
+            .eviction_timing(EvictionTiming::Eager)
+            .build();
+        for i in 0..10 {
+            cache.insert(i.to_string(), i.to_string());
+            assert!(cache.len() <= 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lazy_eviction_overshoots_until_cleaner_runs() {
+        use rustycache::strategy::EvictionTiming;
+
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(3)
+            .ttl(Duration::from_secs(5))
+            .clean_interval(Duration::from_secs(1))
+            .eviction_timing(EvictionTiming::Lazy { slack: 2 })
+            .build();
+        for i in 0..10 {
+            cache.insert(i.to_string(), i.to_string());
+            assert!(cache.len() <= 5);
+        }
+        assert_eq!(cache.len(), 5);
+
+        sleep(Duration::from_millis(1500)).await;
+
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_put_evicting_returns_least_frequent() {
+        let mut cache = create_cache(2, 5, 60);
+        assert_eq!(cache.put_evicting("a".to_string(), "A".to_string()), None);
+        assert_eq!(cache.put_evicting("b".to_string(), "B".to_string()), None);
+        cache.get(&"a".to_string());
+
+        let evicted = cache.put_evicting("c".to_string(), "C".to_string());
+        assert_eq!(evicted, Some(("b".to_string(), "B".to_string())));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_consistent_never_sees_torn_writes() {
+        let mut cache = create_cache(4, 30, 60);
+        cache.insert("a".to_string(), "0".to_string());
+        cache.insert("b".to_string(), "0".to_string());
+
+        // The writer always updates "a" before "b", so a consistent view has b <= a.
+        let mut writer = cache.clone();
+        let handle = std::thread::spawn(move || {
+            for i in 1..=20_000 {
+                writer.insert("a".to_string(), i.to_string());
+                writer.insert("b".to_string(), i.to_string());
+            }
+        });
+
+        let keys = ["a".to_string(), "b".to_string()];
+        while !handle.is_finished() {
+            let values = cache.get_consistent(&keys);
+            let a: u32 = values[0].as_ref().unwrap().parse().unwrap();
+            let b: u32 = values[1].as_ref().unwrap().parse().unwrap();
+            assert!(b <= a, "torn read: a={} b={}", a, b);
+        }
+        handle.join().unwrap();
+
+        assert_eq!(cache.get_consistent(&keys), vec![Some("20000".to_string()), Some("20000".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_cleaner_batch_sweeps_in_bounded_steps() {
+        let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY)
+            .capacity(1000)
+            .ttl(Duration::from_millis(500))
+            .clean_interval(Duration::from_secs(1))
+            .cleaner_batch(100)
+            .build();
+        for i in 0..1000 {
+            cache.insert(i, i);
+        }
+
+        let mut observed = Vec::new();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !cache.is_empty() && std::time::Instant::now() < deadline {
+            let len = cache.len();
+            assert_eq!((1000 - len) % 100, 0, "partial batch observed: len={}", len);
+            if len < 1000 {
+                observed.push(len);
+            }
+            tokio::task::yield_now().await;
+        }
+
+        assert!(cache.is_empty());
+        assert!(!observed.is_empty(), "cleaner never yielded between batches");
+    }
+
+    #[tokio::test]
+    async fn test_stats_through_rustycache() {
+        let mut cache = create_cache(2, 60, 60);
+        assert_eq!(cache.stats(), rustycache::strategy::CacheStats::default());
+
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+        cache.get(&"c".to_string());
+        cache.get(&"missing".to_string());
+        cache.remove(&"c".to_string());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.insertions, 3);
+        assert_eq!(stats.evictions.capacity, 1);
+        assert_eq!(stats.evictions.removed, 1);
+        assert_eq!(stats.evictions.expired, 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_or_insert_with_ttl_uses_supplied_ttl() {
+        let mut cache = create_cache(10, 60, 60);
+        let mut calls = 0;
+
+        let value = cache.get_or_insert_with_ttl("key".to_string(), Duration::from_secs(1), || {
+            calls += 1;
+            "computed".to_string()
+        });
+        assert_eq!(value, "computed");
+
+        let value = cache.get_or_insert_with_ttl("key".to_string(), Duration::from_secs(1), || {
+            calls += 1;
+            "recomputed".to_string()
+        });
+        assert_eq!(value, "computed");
+        assert_eq!(calls, 1);
+
+        sleep(Duration::from_millis(1100)).await;
+        assert_eq!(cache.get(&"key".to_string()), None);
+    }
+
+    #[tokio::test]
+    async fn test_clear_and_stop() {
+        let mut cache = create_cache(10, 1, 1);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+
+        cache.clear_and_stop().await;
+        assert!(cache.is_empty());
+
+        cache.insert("c".to_string(), "3".to_string());
+        sleep(Duration::from_millis(2500)).await;
+        assert_eq!(cache.len(), 1, "a cleaner sweep ran after clear_and_stop");
+    }
+
+    #[tokio::test]
+    async fn test_get_or_insert_with_ttl_computes_once_under_contention() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Barrier};
+
+        let cache = create_cache(10, 60, 60);
+        let computations = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(4));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let mut cache = cache.clone();
+                let computations = Arc::clone(&computations);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    cache.get_or_insert_with_ttl("key".to_string(), Duration::from_secs(60), || {
+                        computations.fetch_add(1, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(100));
+                        "computed".to_string()
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), "computed");
+        }
+        assert_eq!(computations.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_equal_frequency_evicts_in_insertion_order() {
+        let mut cache: Rustycache<u32, u32> = Rustycache::new(
+            5,
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            STRATEGY,
+        );
+        for i in 0..5 {
+            cache.insert(i, i);
+        }
+
+        let evicted: Vec<u32> = (5..8)
+            .map(|i| cache.put_evicting(i, i).map(|(key, _)| key).unwrap())
+            .collect();
+        assert_eq!(evicted, vec![0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_map_values_preserves_keys_and_ttls() {
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("short".to_string(), "abc".to_string());
+        cache.put_with_ttl("brief".to_string(), "abcdef".to_string(), Duration::from_secs(2));
+
+        let mut lengths = cache.map_values(|value| value.len());
+        assert_eq!(lengths.len(), 2);
+        assert_eq!(lengths.get(&"short".to_string()), Some(3));
+        assert_eq!(lengths.get(&"brief".to_string()), Some(6));
+
+        let remaining: std::collections::HashMap<String, Duration> = lengths
+            .snapshot_with_meta()
+            .into_iter()
+            .map(|(key, _, remaining)| (key, remaining))
+            .collect();
+        assert!(remaining["brief"] <= Duration::from_secs(2));
+        assert!(remaining["short"] > Duration::from_secs(50));
+    }
+
+    #[tokio::test]
+    async fn test_frequency_buckets_stay_bounded_under_churn() {
+        use rustycache::strategy::lfu::LFUCache;
+        use rustycache::strategy::CacheStrategy;
+
+        let cache: LFUCache<u32, u32> = LFUCache::new(50, Duration::from_secs(60), Duration::from_millis(50));
+        for round in 0..20u32 {
+            for i in 0..100 {
+                let key = round * 100 + i;
+                cache.put(key, key);
+                for _ in 0..(i % 7) {
+                    cache.get(&key);
+                }
+                if i % 3 == 0 {
+                    cache.remove(&key);
+                }
+            }
+            assert!(cache.frequency_buckets() <= cache.len());
+        }
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(cache.frequency_buckets() <= cache.len());
+        assert!(cache.frequency_buckets() <= 7);
+    }
+
+    #[tokio::test]
+    async fn test_extend_ttl() {
+        let mut cache = create_cache(10, 1, 60);
+        cache.insert("key".to_string(), "value".to_string());
+        assert!(cache.extend_ttl(&"key".to_string(), Duration::from_secs(1)));
+        assert!(!cache.extend_ttl(&"missing".to_string(), Duration::from_secs(1)));
+
+        sleep(Duration::from_millis(1300)).await;
+        assert_eq!(cache.get(&"key".to_string()), Some("value".to_string()));
+
+        sleep(Duration::from_millis(900)).await;
+        assert_eq!(cache.get(&"key".to_string()), None);
+        assert!(!cache.extend_ttl(&"key".to_string(), Duration::from_secs(1)));
+    }
+
+    #[tokio::test]
+    async fn test_peek_victim_matches_eviction() {
+        let mut cache = create_cache(3, 60, 60);
+        assert_eq!(cache.peek_victim(), None);
+
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+        cache.get(&"a".to_string());
+
+        let predicted = cache.peek_victim();
+        assert!(predicted.is_some());
+        assert_eq!(cache.len(), 3);
+
+        let evicted = cache.put_evicting("d".to_string(), "4".to_string()).map(|(key, _)| key);
+        assert_eq!(evicted, predicted);
+    }
+
+    #[tokio::test]
+    async fn test_builder_expiration_policy() {
+        use rustycache::strategy::ExpirationPolicy;
+
+        let build = |policy| -> Rustycache<String, String> {
+            RustycacheBuilder::new(STRATEGY)
+                .ttl(Duration::from_secs(1))
+                .expiration_policy(policy)
+                .build()
+        };
+        let mut sliding = build(ExpirationPolicy::Sliding);
+        let mut absolute = build(ExpirationPolicy::Absolute);
+        assert_eq!(sliding.expiration_policy(), ExpirationPolicy::Sliding);
+        assert_eq!(absolute.expiration_policy(), ExpirationPolicy::Absolute);
+
+        for cache in [&mut sliding, &mut absolute] {
+            cache.insert("key".to_string(), "value".to_string());
+        }
+        sleep(Duration::from_millis(600)).await;
+        assert!(sliding.get(&"key".to_string()).is_some());
+        assert!(absolute.get(&"key".to_string()).is_some());
+
+        sleep(Duration::from_millis(600)).await;
+        assert!(sliding.get(&"key".to_string()).is_some());
+        assert!(absolute.get(&"key".to_string()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_remove_many_aligns_with_input() {
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+
+        let keys = ["c".to_string(), "missing".to_string(), "a".to_string(), "a".to_string()];
+        assert_eq!(
+            cache.remove_many(&keys),
+            vec![Some("3".to_string()), None, Some("1".to_string()), None]
+        );
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&"b".to_string()));
+        assert_eq!(cache.peek_victim(), Some("b".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_check_invariants_after_random_operations() {
+        let mut cache = create_cache(8, 60, 60);
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as usize
+        };
+
+        for _ in 0..2000 {
+            let key = format!("k{}", next() % 16);
+            match next() % 6 {
+                0 | 1 => cache.insert(key, "v".to_string()),
+                2 => {
+                    cache.get(&key);
+                }
+                3 => {
+                    cache.remove(&key);
+                }
+                4 => {
+                    cache.take(&key);
+                }
+                _ => {
+                    cache.remove_many(&[key, format!("k{}", next() % 16)]);
+                }
+            }
+            cache.check_invariants().unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hashmap_style_api() {
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("key".to_string(), "value".to_string());
+        assert_eq!(cache.get(&"key".to_string()), Some("value".to_string()));
+        assert_eq!(cache.remove(&"key".to_string()), Some("value".to_string()));
+        assert_eq!(cache.remove(&"key".to_string()), None);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_or_insert() {
+        let mut cache = create_cache(10, 60, 60);
+        assert_eq!(cache.get_or_insert("key".to_string(), "default".to_string()), "default");
+        assert_eq!(cache.get(&"key".to_string()), Some("default".to_string()));
+
+        assert_eq!(cache.get_or_insert("key".to_string(), "other".to_string()), "default");
+        assert_eq!(cache.get(&"key".to_string()), Some("default".to_string()));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_zero_capacity_stores_nothing() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY).capacity(0).build();
+        cache.insert("a".to_string(), "1".to_string());
+        assert_eq!(cache.put_evicting("b".to_string(), "2".to_string()), None);
+        assert_eq!(cache.get_or_insert("c".to_string(), "3".to_string()), "3");
+
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.get(&"a".to_string()), None);
+        cache.check_invariants().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_touch_matching_keeps_group_alive() {
+        let mut cache = create_cache(10, 1, 60);
+        for key in ["tenant1:a", "tenant1:b", "tenant2:a"] {
+            cache.insert(key.to_string(), "v".to_string());
+        }
+
+        sleep(Duration::from_millis(700)).await;
+        assert_eq!(cache.touch_matching(|key| key.starts_with("tenant1:")), 2);
+
+        sleep(Duration::from_millis(600)).await;
+        assert!(cache.get(&"tenant1:a".to_string()).is_some());
+        assert!(cache.get(&"tenant1:b".to_string()).is_some());
+        assert!(cache.get(&"tenant2:a".to_string()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_write_behind_reaches_sink() {
+        use std::sync::{Arc, Mutex};
+
+        let store: Arc<Mutex<Vec<(String, String)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink_store = Arc::clone(&store);
+        let mut cache = create_cache(10, 60, 60).with_write_behind(
+            move |key, value| {
+                let store = Arc::clone(&sink_store);
+                async move { store.lock().unwrap().push((key, value)) }
+            },
+            100,
+            Duration::from_secs(60),
+        );
+
+        for i in 0..5 {
+            cache.insert(format!("k{}", i), i.to_string());
+        }
+        cache.put_with_ttl("ttl".to_string(), "t".to_string(), Duration::from_secs(5));
+        cache.flush().await;
+
+        let written = store.lock().unwrap().clone();
+        assert_eq!(written.len(), 6);
+        assert_eq!(written[0], ("k0".to_string(), "0".to_string()));
+        assert_eq!(written[5], ("ttl".to_string(), "t".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_to_hashmap_excludes_expired() {
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.put_with_ttl("gone".to_string(), "3".to_string(), Duration::from_millis(100));
+        sleep(Duration::from_millis(200)).await;
+
+        let expected: std::collections::HashMap<String, String> =
+            [("a", "1"), ("b", "2")].into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(cache.to_hashmap(), expected);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_debug_order() {
+        let mut cache = create_cache(10, 60, 60);
+        for key in ["a", "b", "c", "d"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        cache.get(&"a".to_string());
+        cache.get(&"c".to_string());
+        cache.get(&"a".to_string());
+
+        let order: Vec<String> = ["b", "d", "c", "a"].iter().map(|k| k.to_string()).collect();
+        assert_eq!(cache.debug_order(), order);
+        assert_eq!(cache.peek_victim(), order.first().cloned());
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_outcomes() {
+        use rustycache::strategy::GetResult;
+
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("live".to_string(), "1".to_string());
+        cache.put_with_ttl("short".to_string(), "2".to_string(), Duration::from_millis(100));
+        sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(cache.get_detailed(&"live".to_string()), GetResult::Hit("1".to_string()));
+        assert_eq!(cache.get_detailed(&"short".to_string()), GetResult::Expired);
+        assert_eq!(cache.get_detailed(&"short".to_string()), GetResult::Missing);
+        assert_eq!(cache.get_detailed(&"never".to_string()), GetResult::Missing);
+    }
+
+    #[tokio::test]
+    async fn test_watermarks_evict_in_batches() {
+        let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY).watermarks(3, 6).build();
+        for i in 0..6 {
+            cache.insert(i, i);
+        }
+        assert_eq!(cache.len(), 6);
+        assert_eq!(cache.stats().evictions.capacity, 0);
+
+        cache.insert(6, 6);
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.stats().evictions.capacity, 3);
+        assert!(cache.contains(&6));
+        cache.check_invariants().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_await_waits_for_space() {
+        let mut cache = create_cache(2, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+
+        let producer = cache.clone();
+        let handle = tokio::spawn(async move {
+            producer.put_await("c".to_string(), "3".to_string()).await;
+        });
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(!handle.is_finished());
+        assert!(cache.contains(&"a".to_string()));
+        assert!(cache.contains(&"b".to_string()));
+
+        cache.remove(&"a".to_string());
+        tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+        assert!(cache.contains(&"b".to_string()));
+        assert!(cache.contains(&"c".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_with_loader_reads_through_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let loader_calls = Arc::clone(&calls);
+        let mut cache: Rustycache<u32, String> = Rustycache::with_loader(
+            10,
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            rustycache::strategy::StrategyType::LFU,
+            move |key| {
+                loader_calls.fetch_add(1, Ordering::SeqCst);
+                (*key < 100).then(|| format!("value{}", key))
+            },
+        );
+
+        assert_eq!(cache.get(&1), Some("value1".to_string()));
+        assert_eq!(cache.get(&1), Some("value1".to_string()));
+        assert_eq!(cache.get(&2), Some("value2".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        assert_eq!(cache.get(&500), None);
+        assert_eq!(cache.get(&500), None);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_remaining_capacity() {
+        let mut cache = create_cache(3, 60, 60);
+        assert_eq!(cache.remaining_capacity(), Some(3));
+        cache.insert("a".to_string(), "1".to_string());
+        assert_eq!(cache.remaining_capacity(), Some(2));
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+        assert_eq!(cache.remaining_capacity(), Some(0));
+
+        let unbounded: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY).capacity(usize::MAX).build();
+        assert_eq!(unbounded.remaining_capacity(), None);
+        let empty: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY).capacity(0).build();
+        assert_eq!(empty.remaining_capacity(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_downcast_to_lfu_strategy() {
+        use rustycache::strategy::lfu::LFUCache;
+        use rustycache::strategy::lru::LRUCache;
+
+        let mut cache = create_cache(3, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.get(&"a".to_string());
+        cache.get(&"a".to_string());
+
+        let lfu = cache.downcast_ref::<LFUCache<String, String>>().expect("LFU strategy");
+        assert_eq!(lfu.frequency(&"a".to_string()), Some(3));
+        assert_eq!(lfu.frequency(&"missing".to_string()), None);
+        assert!(cache.downcast_ref::<LRUCache<String, String>>().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_amortized_cleanup_reclaims_expired_on_put() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(10)
+            .ttl(Duration::from_millis(50))
+            .clean_interval(Duration::from_secs(3600))
+            .amortized_cleanup(2)
+            .build();
+        cache.stop_cleaner();
+        for i in 0..4 {
+            cache.insert(format!("old{i}"), "v".to_string());
+        }
+        sleep(Duration::from_millis(100)).await;
+
+        cache.put_with_ttl("new0".to_string(), "v".to_string(), Duration::from_secs(60));
+        assert_eq!(cache.len(), 3);
+        cache.put_with_ttl("new1".to_string(), "v".to_string(), Duration::from_secs(60));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().evictions.expired, 4);
+    }
+
+    #[tokio::test]
+    async fn test_update_many_mutates_in_place() {
+        let mut cache: Rustycache<String, u32> = RustycacheBuilder::new(STRATEGY).capacity(4).build();
+        cache.insert("a".to_string(), 1);
+        cache.insert("b".to_string(), 10);
+        cache.insert("c".to_string(), 100);
+
+        let keys = ["a".to_string(), "c".to_string(), "missing".to_string()];
+        cache.update_many(&keys, |_, value| *value += 1);
+
+        assert_eq!(cache.get(&"a".to_string()), Some(2));
+        assert_eq!(cache.get(&"b".to_string()), Some(10));
+        assert_eq!(cache.get(&"c".to_string()), Some(101));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_hasher_round_trips_integer_and_string_keys() {
+        // Runs against aHash when built with `--features fast-hash`.
+        let mut ints: Rustycache<u64, u64> = RustycacheBuilder::new(STRATEGY).capacity(1_000).build();
+        let mut strings: Rustycache<String, u64> = RustycacheBuilder::new(STRATEGY).capacity(1_000).deterministic(true).build();
+        for i in 0..1_000u64 {
+            ints.insert(i, i * 2);
+            strings.insert(format!("key{i}"), i);
+        }
+        for i in 0..1_000u64 {
+            assert_eq!(ints.get(&i), Some(i * 2));
+            assert_eq!(strings.get(&format!("key{i}")), Some(i));
+        }
+        assert_eq!(ints.get(&1_000), None);
+        assert!(ints.check_invariants().is_ok());
+        assert!(strings.check_invariants().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_clear_with_callbacks_visits_every_entry() {
+        use rustycache::strategy::CacheEvent;
+
+        let mut cache = create_cache(5, 60, 60);
+        let mut events = cache.subscribe();
+        for key in ["a", "b", "c"] {
+            cache.insert(key.to_string(), key.to_uppercase());
+        }
+
+        let mut seen = Vec::new();
+        cache.clear_with_callbacks(|key, value| seen.push((key, value)));
+        seen.sort();
+        assert_eq!(seen, vec![
+            ("a".to_string(), "A".to_string()),
+            ("b".to_string(), "B".to_string()),
+            ("c".to_string(), "C".to_string()),
+        ]);
+        assert!(cache.is_empty());
+        assert!(cache.check_invariants().is_ok());
+
+        let removed = std::iter::from_fn(|| events.try_recv().ok())
+            .filter(|event| matches!(event, CacheEvent::Removed(_)))
+            .count();
+        assert_eq!(removed, 3);
+    }
+
+    #[tokio::test]
+    async fn test_hydrate_from_copies_live_entries() {
+        use rustycache::rustycache::HydrateConflict;
+
+        let mut source = create_cache(10, 60, 60);
+        source.put_with_ttl("short".to_string(), "s".to_string(), Duration::from_secs(5));
+        source.put_with_ttl("long".to_string(), "l".to_string(), Duration::from_secs(600));
+        source.put_with_ttl("mid".to_string(), "m".to_string(), Duration::from_secs(60));
+
+        let mut target = create_cache(2, 60, 60);
+        assert_eq!(target.hydrate_from(&source), 3);
+        assert_eq!(target.len(), 2);
+        assert!(!target.contains(&"short".to_string()));
+        let mut meta = target.snapshot_with_meta();
+        meta.sort_by_key(|(_, _, remaining)| *remaining);
+        assert_eq!(meta[0].0, "mid");
+        assert!(meta[0].2 <= Duration::from_secs(60) && meta[0].2 > Duration::from_secs(55));
+        assert_eq!(meta[1].0, "long");
+        assert!(meta[1].2 > Duration::from_secs(590));
+
+        // "mid" outlives the local copy, "long" does not.
+        let mut peer = create_cache(10, 60, 60);
+        peer.put_with_ttl("mid".to_string(), "fresh".to_string(), Duration::from_secs(300));
+        peer.put_with_ttl("long".to_string(), "stale".to_string(), Duration::from_secs(10));
+        assert_eq!(target.hydrate_from(&peer), 1);
+        assert_eq!(target.get(&"mid".to_string()), Some("fresh".to_string()));
+        assert_eq!(target.get(&"long".to_string()), Some("l".to_string()));
+
+        assert_eq!(target.hydrate_from_with(&peer, HydrateConflict::KeepExisting), 0);
+        assert_eq!(target.hydrate_from_with(&peer, HydrateConflict::Overwrite), 2);
+        assert_eq!(target.get(&"long".to_string()), Some("stale".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_capacity_and_ttl_read_back() {
+        let cache = create_cache(7, 42, 60);
+        assert_eq!(cache.capacity(), 7);
+        assert_eq!(cache.ttl(), Duration::from_secs(42));
+    }
+
+    #[tokio::test]
+    async fn test_get_expect_returns_present_value() {
+        let mut cache = create_cache(2, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        assert_eq!(cache.get_expect(&"a".to_string()), "1");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "key not present in cache (missing or expired)")]
+    async fn test_get_expect_panics_on_missing_key() {
+        let mut cache = create_cache(2, 60, 60);
+        cache.get_expect(&"missing".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_expiry_index_stops_at_future_deadlines() {
+        use rustycache::strategy::lfu::LFUCache;
+
+        let mut cache = create_cache(10, 60, 3600);
+        cache.put_with_ttl("a".to_string(), "1".to_string(), Duration::from_millis(20));
+        cache.put_with_ttl("b".to_string(), "2".to_string(), Duration::from_millis(40));
+        cache.put_with_ttl("c".to_string(), "3".to_string(), Duration::from_secs(10));
+        cache.put_with_ttl("d".to_string(), "4".to_string(), Duration::from_secs(20));
+        let buckets = |cache: &Rustycache<String, String>| cache.downcast_ref::<LFUCache<String, String>>().unwrap().expiry_buckets();
+        assert_eq!(buckets(&cache), 4);
+
+        sleep(Duration::from_millis(80)).await;
+        let mut drained: Vec<String> = cache.drain_expired().into_iter().map(|(key, _)| key).collect();
+        drained.sort();
+        assert_eq!(drained, vec!["a", "b"]);
+        // Only the two due buckets were popped; the future ones are untouched.
+        assert_eq!(buckets(&cache), 2);
+
+        // Refreshing a TTL moves the key to its new bucket.
+        assert!(cache.extend_ttl(&"c".to_string(), Duration::from_secs(5)));
+        assert_eq!(buckets(&cache), 2);
+        assert!(cache.check_invariants().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_try_new_rejects_invalid_arguments() {
+        use rustycache::rustycache::CacheConfigError;
+
+        let ok = Rustycache::<String, String>::try_new(10, Duration::from_secs(60), Duration::from_secs(1), STRATEGY);
+        assert!(ok.is_ok());
+
+        let zero_interval = Rustycache::<String, String>::try_new(10, Duration::from_secs(60), Duration::ZERO, STRATEGY);
+        assert_eq!(zero_interval.err(), Some(CacheConfigError::ZeroCleanInterval));
+
+        let huge = Duration::from_secs(u64::MAX);
+        let huge_ttl = Rustycache::<String, String>::try_new(10, huge, Duration::from_secs(1), STRATEGY);
+        assert_eq!(huge_ttl.err(), Some(CacheConfigError::TtlOutOfRange(huge)));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "invalid cache config: clean interval must be non-zero")]
+    async fn test_new_panics_on_zero_clean_interval() {
+        let _cache: Rustycache<String, String> = Rustycache::new(10, Duration::from_secs(60), Duration::ZERO, STRATEGY);
+    }
+
+    #[tokio::test]
+    async fn test_changed_wakes_on_put_of_key() {
+        let mut cache = create_cache(4, 60, 60);
+        let watcher = cache.clone();
+        let waiter = tokio::spawn(async move {
+            watcher.changed(&"k".to_string()).await;
+        });
+        sleep(Duration::from_millis(20)).await;
+
+        cache.insert("other".to_string(), "x".to_string());
+        sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        cache.insert("k".to_string(), "v".to_string());
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.expect("waiter woke").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pinned_entries_survive_eviction() {
+        let mut cache = create_cache(3, 60, 60);
+        for key in ["a", "b", "c"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        assert!(cache.pin(&"a".to_string()));
+        assert!(!cache.pin(&"missing".to_string()));
+        assert_ne!(cache.peek_victim(), Some("a".to_string()));
+
+        for key in ["d", "e", "f"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        assert_eq!(cache.len(), 3);
+        assert!(cache.contains(&"a".to_string()));
+        assert!(!cache.contains(&"b".to_string()));
+        assert!(!cache.contains(&"c".to_string()));
+        assert!(cache.check_invariants().is_ok());
+
+        // With every entry pinned, inserts grow the cache instead of evicting.
+        assert!(cache.pin(&"e".to_string()));
+        assert!(cache.pin(&"f".to_string()));
+        cache.insert("g".to_string(), "g".to_string());
+        assert_eq!(cache.len(), 4);
+        assert!(cache.check_invariants().is_ok());
+
+        cache.unpin(&"a".to_string());
+        cache.insert("h".to_string(), "h".to_string());
+        assert_eq!(cache.len(), 3);
+        assert!(!cache.contains(&"a".to_string()) && !cache.contains(&"g".to_string()));
+        assert!(cache.contains(&"e".to_string()) && cache.contains(&"f".to_string()));
+        assert!(cache.check_invariants().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_compact_rebuilds_structures_after_churn() {
+        use rustycache::strategy::lfu::LFUCache;
+
+        let mut cache = create_cache(2_000, 60, 3600);
+        for i in 0..2_000 {
+            cache.put_with_ttl(format!("k{i}"), "v".to_string(), Duration::from_secs(60 + i));
+        }
+        for i in 0..1_990 {
+            cache.remove(&format!("k{i}"));
+        }
+        let before = cache.allocated_capacity();
+        let buckets = |cache: &Rustycache<String, String>| cache.downcast_ref::<LFUCache<String, String>>().unwrap().expiry_buckets();
+        assert_eq!(buckets(&cache), 10);
+
+        cache.compact();
+        assert!(cache.check_invariants().is_ok());
+        assert!(cache.allocated_capacity() < before);
+        assert_eq!(buckets(&cache), 10);
+        assert_eq!(cache.len(), 10);
+        assert_eq!(cache.get(&"k1995".to_string()), Some("v".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_load_with_caches_with_loader_ttl() {
+        let cache = create_cache(4, 60, 60);
+        let loaded: Result<String, String> = cache
+            .load_with("k".to_string(), |key| {
+                let value = format!("{key}-loaded");
+                async move { Ok((value, Duration::from_secs(5))) }
+            })
+            .await;
+        assert_eq!(loaded, Ok("k-loaded".to_string()));
+        let remaining = cache.remaining_ttl(&"k".to_string()).unwrap();
+        assert!(remaining <= Duration::from_secs(5) && remaining > Duration::from_secs(4));
+
+        // A hit never calls the loader.
+        let hit: Result<String, String> = cache
+            .load_with("k".to_string(), |_| async { panic!("loader called on a hit") })
+            .await;
+        assert_eq!(hit, Ok("k-loaded".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_load_with_error_caches_nothing() {
+        let cache = create_cache(4, 60, 60);
+        let failed: Result<String, String> = cache
+            .load_with("k".to_string(), |_| async { Err("upstream down".to_string()) })
+            .await;
+        assert_eq!(failed, Err("upstream down".to_string()));
+        assert!(!cache.contains(&"k".to_string()));
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_frequency_histogram_counts_keys_per_frequency() {
+        use rustycache::strategy::lfu::LFUCache;
+        use std::collections::BTreeMap;
+
+        let mut cache = create_cache(10, 60, 60);
+        for key in ["a", "b", "c", "d", "e"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        for _ in 0..3 {
+            cache.get(&"a".to_string());
+        }
+        cache.get(&"b".to_string());
+        cache.get(&"c".to_string());
+
+        let lfu = cache.downcast_ref::<LFUCache<String, String>>().unwrap();
+        assert_eq!(lfu.frequency_histogram(), BTreeMap::from([(1, 2), (2, 2), (4, 1)]));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_clear_is_atomic_for_concurrent_observers() {
+        let cache = create_cache(64, 30, 60);
+
+        let mut churner = cache.clone();
+        let handle = std::thread::spawn(move || {
+            for round in 0..2_000 {
+                for i in 0..16 {
+                    churner.insert(format!("k{i}"), round.to_string());
+                }
+                churner.clear();
+            }
+        });
+
+        while !handle.is_finished() {
+            if let Err(message) = cache.check_invariants() {
+                panic!("observed a partially cleared cache: {}", message);
+            }
+            let order = cache.debug_order();
+            assert!(order.len() <= 16);
+        }
+        handle.join().unwrap();
+        assert!(cache.is_empty());
+        assert!(cache.debug_order().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_without_cleaner_is_swept_by_evict_expired() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(10)
+            .ttl(Duration::from_millis(50))
+            .without_cleaner()
+            .build();
+        for i in 0..3 {
+            cache.insert(i.to_string(), i.to_string());
+        }
+        sleep(Duration::from_millis(120)).await;
+        assert_eq!(cache.len(), 3);
+
+        assert_eq!(cache.evict_expired(), 3);
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.evict_expired(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_eviction_filter_veto_redirects_to_next_victim() {
+        let mut cache = create_cache(2, 30, 60);
+        cache.set_eviction_filter(|key: &String, _: &String| key != "a");
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        assert_eq!(cache.peek_victim(), Some("b".to_string()));
+
+        cache.insert("c".to_string(), "3".to_string());
+        assert!(cache.contains(&"a".to_string()));
+        assert!(!cache.contains(&"b".to_string()));
+        assert!(cache.contains(&"c".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_eviction_filter_vetoing_everything_falls_back_to_usual_victim() {
+        let mut cache = create_cache(2, 30, 60);
+        cache.set_eviction_filter(|_: &String, _: &String| false);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains(&"a".to_string()));
+        assert!(cache.check_invariants().is_ok());
+
+        cache.clear_eviction_filter();
+        cache.insert("d".to_string(), "4".to_string());
+        assert!(!cache.contains(&"b".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_get_with_metadata_reports_entry_info() {
+        let mut cache = create_cache(10, 30, 60);
+        let before = chrono::Utc::now();
+        cache.insert("k".to_string(), "v".to_string());
+        cache.get(&"k".to_string());
+
+        let item = cache.get_with_metadata(&"k".to_string()).unwrap();
+        assert_eq!(item.value, "v");
+        assert_eq!(item.frequency, Some(3));
+        assert!(item.inserted_at >= before && item.inserted_at <= chrono::Utc::now());
+        let expires_at = item.expires_at.unwrap();
+        assert!((29_900..=30_000).contains(&(expires_at - item.inserted_at).num_milliseconds()));
+        assert!(item.remaining_ttl > Duration::from_secs(29) && item.remaining_ttl <= Duration::from_secs(30));
+
+        cache.put_permanent("p".to_string(), "v".to_string());
+        let item = cache.get_with_metadata(&"p".to_string()).unwrap();
+        assert_eq!(item.expires_at, None);
+        assert_eq!(item.remaining_ttl, Duration::MAX);
+        assert_eq!(item.frequency, Some(2));
+
+        assert!(cache.get_with_metadata(&"missing".to_string()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_report_reflects_known_state() {
+        let mut cache = create_cache(4, 30, 60);
+        cache.put_with_ttl("short".to_string(), "1".to_string(), Duration::from_millis(50));
+        cache.insert("long".to_string(), "2".to_string());
+        assert_eq!(cache.get(&"long".to_string()), Some("2".to_string()));
+        assert_eq!(cache.get(&"missing".to_string()), None);
+        sleep(Duration::from_millis(100)).await;
+
+        let report = cache.report();
+        assert_eq!(report.strategy_name, "LFU");
+        assert_eq!(report.capacity, 4);
+        assert_eq!(report.ttl, Duration::from_secs(30));
+        assert_eq!(report.len, 2);
+        assert_eq!(report.live_len, 1);
+        assert_eq!(report.stats.hits, 1);
+        assert_eq!(report.stats.misses, 1);
+        assert_eq!(report.stats.insertions, 2);
+        assert_eq!(report.stats, cache.stats());
+    }
+
+    #[tokio::test]
+    async fn test_freeze_suspends_expiry_and_eviction() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(2)
+            .ttl(Duration::from_millis(50))
+            .clean_interval(Duration::from_millis(20))
+            .build();
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+
+        cache.freeze();
+        sleep(Duration::from_millis(120)).await;
+        assert_eq!(cache.get(&"a".to_string()), Some("1".to_string()));
+        assert_eq!(cache.get(&"b".to_string()), Some("2".to_string()));
+        assert_eq!(cache.evict_expired(), 0);
+        cache.insert("c".to_string(), "3".to_string());
+        assert_eq!(cache.len(), 3);
+        assert!(cache.check_invariants().is_ok());
+
+        cache.unfreeze();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.check_invariants().is_ok());
+        assert_eq!(cache.get(&"a".to_string()), None);
+        sleep(Duration::from_millis(120)).await;
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_lru_contents_as_lfu() {
+        use rustycache::strategy::StrategyType;
+
+        let mut lru = Rustycache::new(4, Duration::from_secs(30), Duration::from_secs(60), StrategyType::LRU);
+        lru.insert("a".to_string(), "1".to_string());
+        lru.put_with_ttl("b".to_string(), "2".to_string(), Duration::from_secs(5));
+        lru.put_permanent("c".to_string(), "3".to_string());
+        lru.put_with_ttl("gone".to_string(), "4".to_string(), Duration::from_millis(20));
+        lru.get(&"a".to_string());
+        sleep(Duration::from_millis(50)).await;
+
+        let entries = lru.into_entries();
+        let keys: Vec<&str> = entries.iter().map(|(key, _, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["b", "c", "a"]);
+        let deadlines: Vec<_> = entries.iter().map(|(_, _, expires_at)| *expires_at).collect();
+
+        let mut lfu = Rustycache::from_entries(entries, 4, Duration::from_secs(30), Duration::from_secs(60), STRATEGY);
+        assert_eq!(lfu.len(), 3);
+        assert_eq!(lfu.debug_order(), vec!["b".to_string(), "c".to_string(), "a".to_string()]);
+        assert_eq!(lfu.remaining_ttl(&"c".to_string()), Some(Duration::MAX));
+        let b_left = lfu.remaining_ttl(&"b".to_string()).unwrap();
+        assert!(b_left > Duration::from_secs(4) && b_left <= Duration::from_secs(5));
+        for (key, value) in [("a", "1"), ("b", "2"), ("c", "3")] {
+            assert_eq!(lfu.get(&key.to_string()), Some(value.to_string()));
+        }
+        let rebuilt: Vec<_> = lfu.into_entries().into_iter().map(|(_, _, expires_at)| expires_at).collect();
+        for deadline in deadlines {
+            assert!(rebuilt.contains(&deadline));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_estimated_memory_includes_frequency_buckets() {
+        use rustycache::strategy::StrategyType;
+
+        let mut lfu = create_cache(100, 60, 60);
+        let mut lru: Rustycache<String, String> = Rustycache::new(100, Duration::from_secs(60), Duration::from_secs(60), StrategyType::LRU);
+        let empty = lfu.estimated_memory();
+        for i in 0..50 {
+            lfu.insert(i.to_string(), i.to_string());
+            lru.insert(i.to_string(), i.to_string());
+        }
+        for i in 0..10 {
+            lfu.get(&i.to_string());
+            lru.get(&i.to_string());
+        }
+
+        assert!(lfu.estimated_memory() > empty);
+        assert!(lfu.estimated_memory() > lru.estimated_memory());
+    }
+
+    #[tokio::test]
+    async fn test_reset_frequencies_keeps_values() {
+        use rustycache::strategy::lfu::LFUCache;
+        use std::collections::BTreeMap;
+
+        let mut cache = create_cache(10, 60, 60);
+        for key in ["a", "b", "c"] {
+            cache.insert(key.to_string(), key.to_uppercase());
+        }
+        for _ in 0..5 {
+            cache.get(&"a".to_string());
+        }
+        cache.get(&"b".to_string());
+
+        let lfu = cache.downcast_ref::<LFUCache<String, String>>().unwrap();
+        assert_eq!(lfu.frequency(&"a".to_string()), Some(6));
+        lfu.reset_frequencies();
+        assert_eq!(lfu.frequency_histogram(), BTreeMap::from([(1, 3)]));
+        assert_eq!(lfu.frequency_buckets(), 1);
+        assert_eq!(cache.peek_victim(), Some("c".to_string()));
+        assert!(cache.check_invariants().is_ok());
+
+        for key in ["a", "b", "c"] {
+            assert_eq!(cache.get(&key.to_string()), Some(key.to_uppercase()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expiry_index_does_not_outgrow_entries_under_churn() {
+        use rustycache::strategy::lfu::LFUCache;
+
+        let mut cache = create_cache(10, 60, 3600);
+        let buckets = |cache: &Rustycache<String, String>| cache.downcast_ref::<LFUCache<String, String>>().unwrap().expiry_buckets();
+
+        // Evictions.
+        for i in 0..1_000u64 {
+            cache.put_with_ttl(format!("k{i}"), "v".to_string(), Duration::from_secs(60 + i));
+        }
+        assert!(buckets(&cache) <= cache.len());
+
+        // Overwrites of a single key.
+        for i in 0..1_000u64 {
+            cache.put_with_ttl("hot".to_string(), "v".to_string(), Duration::from_secs(2_000 + i));
+        }
+        assert!(buckets(&cache) <= cache.len());
+
+        // Explicit removals.
+        cache.remove(&"k999".to_string());
+        cache.take(&"k998".to_string());
+        cache.remove_many(&["k997".to_string(), "k996".to_string()]);
+        assert!(buckets(&cache) <= cache.len());
+        assert!(cache.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_frozen_reads_judge_expiry_at_freeze() {
+        use rustycache::strategy::MockClock;
+
+        let clock = MockClock::new(chrono::Utc::now());
+        let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY)
+            .ttl(Duration::from_secs(10))
+            .without_cleaner()
+            .clock(clock.clone())
+            .build();
+        cache.insert(1, 1);
+        cache.insert(2, 2);
+        clock.advance_time(Duration::from_secs(4));
+        cache.freeze();
+        clock.advance_time(Duration::from_secs(20));
+
+        cache.update_many(&[1], |_, value| *value += 10);
+        assert_eq!(cache.remaining_ttl(&1), Some(Duration::from_secs(6)));
+        assert_eq!(cache.count_by(|_| ()), [((), 2)].into_iter().collect());
+        let mut snapshot = cache.snapshot_with_meta();
+        snapshot.sort();
+        assert_eq!(snapshot, vec![(1, 11, Duration::from_secs(6)), (2, 2, Duration::from_secs(6))]);
+        assert_eq!(cache.take(&2), Some(2));
+    }
 }
@@ -2,7 +2,9 @@
 mod lru_tests {
     use std::time::Duration;
     use tokio::time::sleep;
-    use rustycache::rustycache::Rustycache;
+    use rustycache::rustycache::{Rustycache, RustycacheBuilder};
+
+    const STRATEGY: rustycache::strategy::StrategyType = rustycache::strategy::StrategyType::LRU;
 
     fn create_cache(capacity: usize, ttl_secs: u64, interval_secs: u64) -> Rustycache<String, String> {
         Rustycache::new(capacity, Duration::from_secs(ttl_secs), Duration::from_secs(interval_secs), rustycache::strategy::StrategyType::LRU)
@@ -11,7 +13,7 @@ mod lru_tests {
     #[tokio::test]
     async fn test_insert_and_get() {
         let mut cache = create_cache(2, 5, 60);
-        cache.put("a".to_string(), "value1".to_string());
+        cache.insert("a".to_string(), "value1".to_string());
 
         assert_eq!(cache.get(&"a".to_string()), Some("value1".to_string()));
         assert!(cache.contains(&"a".to_string()));
@@ -21,10 +23,10 @@ mod lru_tests {
     #[tokio::test]
     async fn test_lru_eviction() {
         let mut cache = create_cache(2, 5, 60);
-        cache.put("a".to_string(), "A".to_string());
-        cache.put("b".to_string(), "B".to_string());
+        cache.insert("a".to_string(), "A".to_string());
+        cache.insert("b".to_string(), "B".to_string());
         cache.get(&"a".to_string()); // 'a' becomes recently used
-        cache.put("c".to_string(), "C".to_string()); // Should evict 'b'
+        cache.insert("c".to_string(), "C".to_string()); // Should evict 'b'
 
         assert!(cache.get(&"a".to_string()).is_some());
         assert!(cache.get(&"b".to_string()).is_none()); // evicted
@@ -34,7 +36,7 @@ mod lru_tests {
     #[tokio::test]
     async fn test_expiration_behavior() {
         let mut cache = create_cache(2, 1, 60);
-        cache.put("x".to_string(), "expire_me".to_string());
+        cache.insert("x".to_string(), "expire_me".to_string());
 
         assert_eq!(cache.get(&"x".to_string()), Some("expire_me".to_string()));
         sleep(Duration::from_secs(2)).await;
@@ -45,9 +47,9 @@ mod lru_tests {
     #[tokio::test]
     async fn test_clear_and_remove() {
         let mut cache = create_cache(3, 5, 60);
-        cache.put("a".to_string(), "1".to_string());
-        cache.put("b".to_string(), "2".to_string());
-        cache.put("c".to_string(), "3".to_string());
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
 
         cache.remove(&"b".to_string());
         assert_eq!(cache.get(&"b".to_string()), None);
@@ -61,7 +63,7 @@ mod lru_tests {
     #[tokio::test]
     async fn test_cleaner_removes_expired() {
         let mut cache = create_cache(2, 1, 1); // TTL = 1s, cleaner every 1s
-        cache.put("k1".to_string(), "v1".to_string());
+        cache.insert("k1".to_string(), "v1".to_string());
 
         sleep(Duration::from_secs(2)).await; // Let it expire
 
@@ -72,15 +74,15 @@ mod lru_tests {
     #[tokio::test]
     async fn test_eviction_order_preserved() {
         let mut cache = create_cache(3, 5, 60);
-        cache.put("1".to_string(), "v1".to_string());
-        cache.put("2".to_string(), "v2".to_string());
-        cache.put("3".to_string(), "v3".to_string());
+        cache.insert("1".to_string(), "v1".to_string());
+        cache.insert("2".to_string(), "v2".to_string());
+        cache.insert("3".to_string(), "v3".to_string());
 
         // Access "1" to make it recently used
         cache.get(&"1".to_string());
 
         // Add another entry to trigger eviction
-        cache.put("4".to_string(), "v4".to_string());
+        cache.insert("4".to_string(), "v4".to_string());
 
         assert!(cache.get(&"1".to_string()).is_some()); // Not evicted
         assert!(cache.get(&"2".to_string()).is_none()); // Least recently used
@@ -101,4 +103,1854 @@ mod lru_tests {
         cache.start_cleaner(Duration::from_secs(1)); // just ensure no panic
         tokio::time::sleep(Duration::from_millis(100)).await;
     }
+
+    #[tokio::test]
+    async fn test_snapshot_with_meta_reports_remaining_ttl() {
+        let mut cache = create_cache(3, 10, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+
+        let mut snapshot = cache.snapshot_with_meta();
+        snapshot.sort_by(|x, y| x.0.cmp(&y.0));
+
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].0, "a");
+        assert_eq!(snapshot[0].1, "1");
+        for (_, _, remaining) in &snapshot {
+            assert!(*remaining <= Duration::from_secs(10));
+            assert!(*remaining > Duration::from_secs(9));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_permanent_survives_ttl() {
+        let mut cache = create_cache(3, 1, 1);
+        cache.put_permanent("pinned".to_string(), "forever".to_string());
+        cache.insert("short".to_string(), "lived".to_string());
+
+        sleep(Duration::from_secs(3)).await;
+
+        assert_eq!(cache.get(&"pinned".to_string()), Some("forever".to_string()));
+        assert_eq!(cache.get(&"short".to_string()), None);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shrink_to_fit_after_clear() {
+        let mut cache = create_cache(1000, 5, 60);
+        for i in 0..1000 {
+            cache.insert(i.to_string(), i.to_string());
+        }
+        let filled = cache.allocated_capacity();
+        assert!(filled >= 1000);
+
+        cache.clear();
+        cache.shrink_to_fit();
+
+        assert!(cache.allocated_capacity() < filled);
+    }
+
+    #[tokio::test]
+    async fn test_take_removes_and_returns() {
+        let mut cache = create_cache(2, 5, 60);
+        cache.insert("a".to_string(), "A".to_string());
+
+        assert_eq!(cache.take(&"a".to_string()), Some("A".to_string()));
+        assert!(!cache.contains(&"a".to_string()));
+        assert_eq!(cache.take(&"a".to_string()), None);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_take_expired_returns_none() {
+        let mut cache = create_cache(2, 1, 60);
+        cache.insert("a".to_string(), "A".to_string());
+
+        sleep(Duration::from_secs(2)).await;
+
+        assert_eq!(cache.take(&"a".to_string()), None);
+        assert!(!cache.contains(&"a".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_receives_events_in_order() {
+        use rustycache::strategy::CacheEvent;
+
+        let mut cache = create_cache(2, 1, 60);
+        let mut events = cache.subscribe();
+
+        cache.insert("a".to_string(), "A".to_string());
+        cache.insert("b".to_string(), "B".to_string());
+        cache.insert("c".to_string(), "C".to_string()); // evicts "a"
+        cache.remove(&"b".to_string());
+        sleep(Duration::from_secs(2)).await;
+        assert_eq!(cache.get(&"c".to_string()), None);
+
+        let expected = vec![
+            CacheEvent::Inserted("a".to_string()),
+            CacheEvent::Inserted("b".to_string()),
+            CacheEvent::Evicted("a".to_string()),
+            CacheEvent::Inserted("c".to_string()),
+            CacheEvent::Removed("b".to_string()),
+            CacheEvent::Expired("c".to_string()),
+        ];
+        for event in expected {
+            assert_eq!(events.recv().await.unwrap(), event);
+        }
+    }
+
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn test_canonical_name_and_alias() {
+        use rustycache::strategy::StrategyType;
+
+        let mut canonical: rustycache::Rustycache<String, String> =
+            rustycache::Rustycache::new(2, Duration::from_secs(5), Duration::from_secs(60), StrategyType::LRU);
+        let mut alias: rustycache::Easycache<String, String> =
+            rustycache::Easycache::new(2, Duration::from_secs(5), Duration::from_secs(60), StrategyType::LRU);
+
+        canonical.insert("a".to_string(), "A".to_string());
+        alias.insert("a".to_string(), "A".to_string());
+
+        assert_eq!(canonical.get(&"a".to_string()), alias.get(&"a".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_get_does_not_extend_absolute_ttl() {
+        let mut cache = create_cache(2, 2, 60);
+        cache.insert("a".to_string(), "A".to_string());
+
+        for _ in 0..3 {
+            sleep(Duration::from_millis(500)).await;
+            assert_eq!(cache.get(&"a".to_string()), Some("A".to_string()));
+        }
+        sleep(Duration::from_millis(700)).await; // 2.2s after the write
+
+        assert_eq!(cache.get(&"a".to_string()), None);
+    }
+
+    #[tokio::test]
+    async fn test_sliding_policy_extends_ttl_on_get() {
+        use rustycache::strategy::ExpirationPolicy;
+
+        let mut cache = create_cache(2, 2, 60);
+        cache.set_expiration_policy(ExpirationPolicy::Sliding);
+        cache.insert("a".to_string(), "A".to_string());
+
+        for _ in 0..4 {
+            sleep(Duration::from_millis(800)).await;
+            assert_eq!(cache.get(&"a".to_string()), Some("A".to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prune_to_keeps_most_recent() {
+        let mut cache = create_cache(5, 5, 60);
+        for key in ["a", "b", "c", "d", "e"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        cache.get(&"a".to_string());
+
+        assert_eq!(cache.prune_to(3), 2);
+        assert_eq!(cache.len(), 3);
+        assert!(cache.contains(&"a".to_string()));
+        assert!(!cache.contains(&"b".to_string()));
+        assert!(!cache.contains(&"c".to_string()));
+
+        assert_eq!(cache.prune_to(5), 0);
+        assert_eq!(cache.prune_to(0), 3);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reserve_avoids_rehash_during_bulk_load() {
+        let mut cache = create_cache(1000, 5, 60);
+        cache.reserve(1000);
+        let reserved = cache.allocated_capacity();
+        assert!(reserved >= 1000);
+
+        for i in 0..1000 {
+            cache.insert(i.to_string(), i.to_string());
+        }
+
+        assert_eq!(cache.allocated_capacity(), reserved);
+        assert_eq!(cache.len(), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_get_if_fresh_within() {
+        let mut cache = create_cache(3, 10, 60);
+        cache.insert("old".to_string(), "1".to_string());
+        sleep(Duration::from_millis(1200)).await;
+        cache.insert("new".to_string(), "2".to_string());
+
+        let max_age = Duration::from_secs(1);
+        assert_eq!(cache.get_if_fresh_within(&"new".to_string(), max_age), Some("2".to_string()));
+        assert_eq!(cache.get_if_fresh_within(&"old".to_string(), max_age), None);
+        assert_eq!(cache.get(&"old".to_string()), Some("1".to_string())); // still unexpired
+    }
+
+    #[tokio::test]
+    async fn test_drain_expired_returns_expired_entries() {
+        let mut cache = create_cache(4, 1, 60);
+        cache.insert("a".to_string(), "A".to_string());
+        cache.insert("b".to_string(), "B".to_string());
+        cache.put_permanent("live".to_string(), "L".to_string());
+
+        sleep(Duration::from_secs(2)).await;
+
+        let mut drained = cache.drain_expired();
+        drained.sort();
+        assert_eq!(drained, vec![
+            ("a".to_string(), "A".to_string()),
+            ("b".to_string(), "B".to_string()),
+        ]);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&"live".to_string()));
+        assert!(cache.drain_expired().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_eager_eviction_never_exceeds_capacity() {
+        use rustycache::strategy::EvictionTiming;
+
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(3)
+            .ttl(Duration::from_secs(5))
+            .clean_interval(Duration::from_secs(60))
+            .eviction_timing(EvictionTiming::Eager)
+            .build();
+        for i in 0..10 {
+            cache.insert(i.to_string(), i.to_string());
+            assert!(cache.len() <= 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lazy_eviction_overshoots_until_cleaner_runs() {
+        use rustycache::strategy::EvictionTiming;
+
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(3)
+            .ttl(Duration::from_secs(5))
+            .clean_interval(Duration::from_secs(1))
+            .eviction_timing(EvictionTiming::Lazy { slack: 2 })
+            .build();
+        for i in 0..10 {
+            cache.insert(i.to_string(), i.to_string());
+            assert!(cache.len() <= 5);
+        }
+        assert_eq!(cache.len(), 5);
+
+        sleep(Duration::from_millis(1500)).await;
+
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_count_by_prefix() {
+        let mut cache = create_cache(10, 5, 60);
+        for key in ["acme:1", "acme:2", "acme:3", "globex:1", "globex:2", "initech:1"] {
+            cache.insert(key.to_string(), "v".to_string());
+        }
+
+        let counts = cache.count_by(|key| key.split(':').next().unwrap().to_string());
+
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts["acme"], 3);
+        assert_eq!(counts["globex"], 2);
+        assert_eq!(counts["initech"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_put_evicting_returns_lru_victim() {
+        let mut cache = create_cache(2, 5, 60);
+        assert_eq!(cache.put_evicting("a".to_string(), "A".to_string()), None);
+        assert_eq!(cache.put_evicting("b".to_string(), "B".to_string()), None);
+        cache.get(&"a".to_string());
+
+        let evicted = cache.put_evicting("c".to_string(), "C".to_string());
+        assert_eq!(evicted, Some(("b".to_string(), "B".to_string())));
+        assert_eq!(cache.put_evicting("c".to_string(), "C2".to_string()), None); // overwrite
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_get_consistent_never_sees_torn_writes() {
+        let mut cache = create_cache(4, 30, 60);
+        cache.insert("a".to_string(), "0".to_string());
+        cache.insert("b".to_string(), "0".to_string());
+
+        // The writer always updates "a" before "b", so a consistent view has b <= a.
+        let mut writer = cache.clone();
+        let handle = std::thread::spawn(move || {
+            for i in 1..=20_000 {
+                writer.insert("a".to_string(), i.to_string());
+                writer.insert("b".to_string(), i.to_string());
+            }
+        });
+
+        let keys = ["a".to_string(), "b".to_string()];
+        while !handle.is_finished() {
+            let values = cache.get_consistent(&keys);
+            let a: u32 = values[0].as_ref().unwrap().parse().unwrap();
+            let b: u32 = values[1].as_ref().unwrap().parse().unwrap();
+            assert!(b <= a, "torn read: a={} b={}", a, b);
+        }
+        handle.join().unwrap();
+
+        assert_eq!(cache.get_consistent(&keys), vec![Some("20000".to_string()), Some("20000".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_get_key_value_returns_stored_key() {
+        #[derive(Clone, Debug)]
+        struct Key {
+            id: u32,
+            label: &'static str,
+        }
+        impl PartialEq for Key {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
+        }
+        impl Eq for Key {}
+        impl std::hash::Hash for Key {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.id.hash(state);
+            }
+        }
+
+        let mut cache: Rustycache<Key, String> = Rustycache::new(
+            2,
+            Duration::from_secs(5),
+            Duration::from_secs(60),
+            rustycache::strategy::StrategyType::LRU,
+        );
+        cache.insert(Key { id: 1, label: "stored" }, "value".to_string());
+
+        let (key, value) = cache.get_key_value(&Key { id: 1, label: "lookup" }).unwrap();
+        assert_eq!(key.label, "stored");
+        assert_eq!(value, "value");
+        assert!(cache.get_key_value(&Key { id: 2, label: "missing" }).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cleaner_batch_sweeps_in_bounded_steps() {
+        let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY)
+            .capacity(1000)
+            .ttl(Duration::from_millis(500))
+            .clean_interval(Duration::from_secs(1))
+            .cleaner_batch(100)
+            .build();
+        for i in 0..1000 {
+            cache.insert(i, i);
+        }
+
+        let mut observed = Vec::new();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !cache.is_empty() && std::time::Instant::now() < deadline {
+            let len = cache.len();
+            assert_eq!((1000 - len) % 100, 0, "partial batch observed: len={}", len);
+            if len < 1000 {
+                observed.push(len);
+            }
+            tokio::task::yield_now().await;
+        }
+
+        assert!(cache.is_empty());
+        assert!(!observed.is_empty(), "cleaner never yielded between batches");
+    }
+
+    #[tokio::test]
+    async fn test_stats_through_rustycache() {
+        let mut cache = create_cache(2, 60, 60);
+        assert_eq!(cache.stats(), rustycache::strategy::CacheStats::default());
+
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+        cache.get(&"c".to_string());
+        cache.get(&"missing".to_string());
+        cache.remove(&"c".to_string());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.insertions, 3);
+        assert_eq!(stats.evictions.capacity, 1);
+        assert_eq!(stats.evictions.removed, 1);
+        assert_eq!(stats.evictions.expired, 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_or_insert_with_ttl_uses_supplied_ttl() {
+        let mut cache = create_cache(10, 60, 60);
+        let mut calls = 0;
+
+        let value = cache.get_or_insert_with_ttl("key".to_string(), Duration::from_secs(1), || {
+            calls += 1;
+            "computed".to_string()
+        });
+        assert_eq!(value, "computed");
+
+        let value = cache.get_or_insert_with_ttl("key".to_string(), Duration::from_secs(1), || {
+            calls += 1;
+            "recomputed".to_string()
+        });
+        assert_eq!(value, "computed");
+        assert_eq!(calls, 1);
+
+        sleep(Duration::from_millis(1100)).await;
+        assert_eq!(cache.get(&"key".to_string()), None);
+    }
+
+    #[tokio::test]
+    async fn test_remove_absent_key_skips_order_scan() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static COMPARISONS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Clone, Debug)]
+        struct Key(u32);
+        impl std::hash::Hash for Key {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.0.hash(state);
+            }
+        }
+        impl PartialEq for Key {
+            fn eq(&self, other: &Self) -> bool {
+                COMPARISONS.fetch_add(1, Ordering::Relaxed);
+                self.0 == other.0
+            }
+        }
+        impl Eq for Key {}
+
+        let mut cache: Rustycache<Key, u32> = Rustycache::new(
+            1000,
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            rustycache::strategy::StrategyType::LRU,
+        );
+        for i in 0..1000 {
+            cache.insert(Key(i), i);
+        }
+
+        COMPARISONS.store(0, Ordering::Relaxed);
+        cache.remove(&Key(5000));
+        assert!(COMPARISONS.load(Ordering::Relaxed) < 10);
+        assert_eq!(cache.len(), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_clear_and_stop() {
+        let mut cache = create_cache(10, 1, 1);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+
+        cache.clear_and_stop().await;
+        assert!(cache.is_empty());
+
+        cache.insert("c".to_string(), "3".to_string());
+        sleep(Duration::from_millis(2500)).await;
+        assert_eq!(cache.len(), 1, "a cleaner sweep ran after clear_and_stop");
+    }
+
+    #[tokio::test]
+    async fn test_get_or_insert_with_ttl_computes_once_under_contention() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Barrier};
+
+        let cache = create_cache(10, 60, 60);
+        let computations = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(4));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let mut cache = cache.clone();
+                let computations = Arc::clone(&computations);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    cache.get_or_insert_with_ttl("key".to_string(), Duration::from_secs(60), || {
+                        computations.fetch_add(1, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(100));
+                        "computed".to_string()
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), "computed");
+        }
+        assert_eq!(computations.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_map_values_preserves_keys_and_ttls() {
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("short".to_string(), "abc".to_string());
+        cache.put_with_ttl("brief".to_string(), "abcdef".to_string(), Duration::from_secs(2));
+
+        let mut lengths = cache.map_values(|value| value.len());
+        assert_eq!(lengths.len(), 2);
+        assert_eq!(lengths.get(&"short".to_string()), Some(3));
+        assert_eq!(lengths.get(&"brief".to_string()), Some(6));
+
+        let remaining: std::collections::HashMap<String, Duration> = lengths
+            .snapshot_with_meta()
+            .into_iter()
+            .map(|(key, _, remaining)| (key, remaining))
+            .collect();
+        assert!(remaining["brief"] <= Duration::from_secs(2));
+        assert!(remaining["short"] > Duration::from_secs(50));
+    }
+
+    #[tokio::test]
+    async fn test_extend_ttl() {
+        let mut cache = create_cache(10, 1, 60);
+        cache.insert("key".to_string(), "value".to_string());
+        assert!(cache.extend_ttl(&"key".to_string(), Duration::from_secs(1)));
+        assert!(!cache.extend_ttl(&"missing".to_string(), Duration::from_secs(1)));
+
+        sleep(Duration::from_millis(1300)).await;
+        assert_eq!(cache.get(&"key".to_string()), Some("value".to_string()));
+
+        sleep(Duration::from_millis(900)).await;
+        assert_eq!(cache.get(&"key".to_string()), None);
+        assert!(!cache.extend_ttl(&"key".to_string(), Duration::from_secs(1)));
+    }
+
+    #[tokio::test]
+    async fn test_peek_victim_matches_eviction() {
+        let mut cache = create_cache(3, 60, 60);
+        assert_eq!(cache.peek_victim(), None);
+
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+        cache.get(&"a".to_string());
+
+        let predicted = cache.peek_victim();
+        assert!(predicted.is_some());
+        assert_eq!(cache.len(), 3);
+
+        let evicted = cache.put_evicting("d".to_string(), "4".to_string()).map(|(key, _)| key);
+        assert_eq!(evicted, predicted);
+    }
+
+    #[tokio::test]
+    async fn test_builder_expiration_policy() {
+        use rustycache::strategy::ExpirationPolicy;
+
+        let build = |policy| -> Rustycache<String, String> {
+            RustycacheBuilder::new(STRATEGY)
+                .ttl(Duration::from_secs(1))
+                .expiration_policy(policy)
+                .build()
+        };
+        let mut sliding = build(ExpirationPolicy::Sliding);
+        let mut absolute = build(ExpirationPolicy::Absolute);
+        assert_eq!(sliding.expiration_policy(), ExpirationPolicy::Sliding);
+        assert_eq!(absolute.expiration_policy(), ExpirationPolicy::Absolute);
+
+        for cache in [&mut sliding, &mut absolute] {
+            cache.insert("key".to_string(), "value".to_string());
+        }
+        sleep(Duration::from_millis(600)).await;
+        assert!(sliding.get(&"key".to_string()).is_some());
+        assert!(absolute.get(&"key".to_string()).is_some());
+
+        sleep(Duration::from_millis(600)).await;
+        assert!(sliding.get(&"key".to_string()).is_some());
+        assert!(absolute.get(&"key".to_string()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_remove_many_aligns_with_input() {
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+
+        let keys = ["c".to_string(), "missing".to_string(), "a".to_string(), "a".to_string()];
+        assert_eq!(
+            cache.remove_many(&keys),
+            vec![Some("3".to_string()), None, Some("1".to_string()), None]
+        );
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&"b".to_string()));
+        assert_eq!(cache.peek_victim(), Some("b".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_check_invariants_after_random_operations() {
+        let mut cache = create_cache(8, 60, 60);
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as usize
+        };
+
+        for _ in 0..2000 {
+            let key = format!("k{}", next() % 16);
+            match next() % 6 {
+                0 | 1 => cache.insert(key, "v".to_string()),
+                2 => {
+                    cache.get(&key);
+                }
+                3 => {
+                    cache.remove(&key);
+                }
+                4 => {
+                    cache.take(&key);
+                }
+                _ => {
+                    cache.remove_many(&[key, format!("k{}", next() % 16)]);
+                }
+            }
+            cache.check_invariants().unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hashmap_style_api() {
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("key".to_string(), "value".to_string());
+        assert_eq!(cache.get(&"key".to_string()), Some("value".to_string()));
+        assert_eq!(cache.remove(&"key".to_string()), Some("value".to_string()));
+        assert_eq!(cache.remove(&"key".to_string()), None);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_or_insert() {
+        let mut cache = create_cache(10, 60, 60);
+        assert_eq!(cache.get_or_insert("key".to_string(), "default".to_string()), "default");
+        assert_eq!(cache.get(&"key".to_string()), Some("default".to_string()));
+
+        assert_eq!(cache.get_or_insert("key".to_string(), "other".to_string()), "default");
+        assert_eq!(cache.get(&"key".to_string()), Some("default".to_string()));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_zero_capacity_stores_nothing() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY).capacity(0).build();
+        cache.insert("a".to_string(), "1".to_string());
+        assert_eq!(cache.put_evicting("b".to_string(), "2".to_string()), None);
+        assert_eq!(cache.get_or_insert("c".to_string(), "3".to_string()), "3");
+
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.get(&"a".to_string()), None);
+        cache.check_invariants().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_touch_matching_keeps_group_alive() {
+        let mut cache = create_cache(10, 1, 60);
+        for key in ["tenant1:a", "tenant1:b", "tenant2:a"] {
+            cache.insert(key.to_string(), "v".to_string());
+        }
+
+        sleep(Duration::from_millis(700)).await;
+        assert_eq!(cache.touch_matching(|key| key.starts_with("tenant1:")), 2);
+
+        sleep(Duration::from_millis(600)).await;
+        assert!(cache.get(&"tenant1:a".to_string()).is_some());
+        assert!(cache.get(&"tenant1:b".to_string()).is_some());
+        assert!(cache.get(&"tenant2:a".to_string()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_write_behind_reaches_sink() {
+        use std::sync::{Arc, Mutex};
+
+        let store: Arc<Mutex<Vec<(String, String)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink_store = Arc::clone(&store);
+        let mut cache = create_cache(10, 60, 60).with_write_behind(
+            move |key, value| {
+                let store = Arc::clone(&sink_store);
+                async move { store.lock().unwrap().push((key, value)) }
+            },
+            100,
+            Duration::from_secs(60),
+        );
+
+        for i in 0..5 {
+            cache.insert(format!("k{}", i), i.to_string());
+        }
+        cache.put_with_ttl("ttl".to_string(), "t".to_string(), Duration::from_secs(5));
+        cache.flush().await;
+
+        let written = store.lock().unwrap().clone();
+        assert_eq!(written.len(), 6);
+        assert_eq!(written[0], ("k0".to_string(), "0".to_string()));
+        assert_eq!(written[5], ("ttl".to_string(), "t".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_write_behind_with_zero_interval_still_flushes() {
+        use std::sync::{Arc, Mutex};
+
+        let store: Arc<Mutex<Vec<(String, String)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink_store = Arc::clone(&store);
+        let mut cache = create_cache(10, 60, 60).with_write_behind(
+            move |key, value| {
+                let store = Arc::clone(&sink_store);
+                async move { store.lock().unwrap().push((key, value)) }
+            },
+            100,
+            Duration::ZERO,
+        );
+
+        cache.insert("a".to_string(), "1".to_string());
+        cache.flush().await;
+        assert_eq!(store.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_write_behind_skips_zero_capacity() {
+        use std::sync::{Arc, Mutex};
+
+        let store: Arc<Mutex<Vec<(String, String)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink_store = Arc::clone(&store);
+        let mut cache = create_cache(0, 60, 60).with_write_behind(
+            move |key, value| {
+                let store = Arc::clone(&sink_store);
+                async move { store.lock().unwrap().push((key, value)) }
+            },
+            100,
+            Duration::from_secs(60),
+        );
+
+        cache.insert("a".to_string(), "1".to_string());
+        cache.put_permanent("b".to_string(), "2".to_string());
+        cache.put_evicting_all("c".to_string(), "3".to_string());
+        cache.flush().await;
+
+        assert_eq!(cache.len(), 0);
+        assert!(store.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_loaded_values_skip_write_behind() {
+        use std::sync::{Arc, Mutex};
+
+        let store: Arc<Mutex<Vec<(u32, u32)>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink_store = Arc::clone(&store);
+        let mut cache = Rustycache::with_loader(10, Duration::from_secs(60), Duration::from_secs(60), STRATEGY, |key: &u32| Some(key * 10))
+            .with_write_behind(
+                move |key, value| {
+                    let store = Arc::clone(&sink_store);
+                    async move { store.lock().unwrap().push((key, value)) }
+                },
+                100,
+                Duration::from_secs(60),
+            );
+
+        assert_eq!(cache.get(&7), Some(70));
+        assert!(cache.contains(&7));
+        cache.insert(1, 1);
+        cache.flush().await;
+
+        assert_eq!(*store.lock().unwrap(), vec![(1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_to_hashmap_excludes_expired() {
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.put_with_ttl("gone".to_string(), "3".to_string(), Duration::from_millis(100));
+        sleep(Duration::from_millis(200)).await;
+
+        let expected: std::collections::HashMap<String, String> =
+            [("a", "1"), ("b", "2")].into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(cache.to_hashmap(), expected);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_debug_order() {
+        let mut cache = create_cache(10, 60, 60);
+        for key in ["a", "b", "c", "d"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        cache.get(&"a".to_string());
+        cache.get(&"c".to_string());
+        cache.get(&"a".to_string());
+
+        let order: Vec<String> = ["b", "d", "c", "a"].iter().map(|k| k.to_string()).collect();
+        assert_eq!(cache.debug_order(), order);
+        assert_eq!(cache.peek_victim(), order.first().cloned());
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_outcomes() {
+        use rustycache::strategy::GetResult;
+
+        let mut cache = create_cache(10, 60, 60);
+        cache.insert("live".to_string(), "1".to_string());
+        cache.put_with_ttl("short".to_string(), "2".to_string(), Duration::from_millis(100));
+        sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(cache.get_detailed(&"live".to_string()), GetResult::Hit("1".to_string()));
+        assert_eq!(cache.get_detailed(&"short".to_string()), GetResult::Expired);
+        assert_eq!(cache.get_detailed(&"short".to_string()), GetResult::Missing);
+        assert_eq!(cache.get_detailed(&"never".to_string()), GetResult::Missing);
+    }
+
+    #[tokio::test]
+    async fn test_watermarks_evict_in_batches() {
+        let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY).watermarks(3, 6).build();
+        for i in 0..6 {
+            cache.insert(i, i);
+        }
+        assert_eq!(cache.len(), 6);
+        assert_eq!(cache.stats().evictions.capacity, 0);
+
+        cache.insert(6, 6);
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.stats().evictions.capacity, 3);
+        assert!(cache.contains(&6));
+        cache.check_invariants().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_await_waits_for_space() {
+        let mut cache = create_cache(2, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+
+        let producer = cache.clone();
+        let handle = tokio::spawn(async move {
+            producer.put_await("c".to_string(), "3".to_string()).await;
+        });
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(!handle.is_finished());
+        assert!(cache.contains(&"a".to_string()));
+        assert!(cache.contains(&"b".to_string()));
+
+        cache.remove(&"a".to_string());
+        tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+        assert!(cache.contains(&"b".to_string()));
+        assert!(cache.contains(&"c".to_string()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_stats_json_round_trips() {
+        use rustycache::strategy::CacheStats;
+
+        let mut cache = create_cache(1, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.get(&"b".to_string());
+        cache.get(&"a".to_string());
+
+        let parsed: CacheStats = serde_json::from_str(&cache.stats_json()).unwrap();
+        assert_eq!(parsed, cache.stats());
+        assert_eq!(parsed.hits, 1);
+        assert_eq!(parsed.misses, 1);
+        assert_eq!(parsed.evictions.capacity, 1);
+    }
+
+    #[tokio::test]
+    async fn test_with_loader_reads_through_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let loader_calls = Arc::clone(&calls);
+        let mut cache: Rustycache<u32, String> = Rustycache::with_loader(
+            10,
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            rustycache::strategy::StrategyType::LRU,
+            move |key| {
+                loader_calls.fetch_add(1, Ordering::SeqCst);
+                (*key < 100).then(|| format!("value{}", key))
+            },
+        );
+
+        assert_eq!(cache.get(&1), Some("value1".to_string()));
+        assert_eq!(cache.get(&1), Some("value1".to_string()));
+        assert_eq!(cache.get(&2), Some("value2".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        assert_eq!(cache.get(&500), None);
+        assert_eq!(cache.get(&500), None);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_remaining_capacity() {
+        let mut cache = create_cache(3, 60, 60);
+        assert_eq!(cache.remaining_capacity(), Some(3));
+        cache.insert("a".to_string(), "1".to_string());
+        assert_eq!(cache.remaining_capacity(), Some(2));
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+        assert_eq!(cache.remaining_capacity(), Some(0));
+
+        let unbounded: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY).capacity(usize::MAX).build();
+        assert_eq!(unbounded.remaining_capacity(), None);
+        let empty: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY).capacity(0).build();
+        assert_eq!(empty.remaining_capacity(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_remaining_capacity_of_zero_capacity_cache() {
+        // synth-152 asked for `None` at capacity 0, but synth-141 made such a
+        // cache store nothing rather than act unbounded, so it is full.
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY).capacity(0).build();
+        cache.insert("a".to_string(), "1".to_string());
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.remaining_capacity(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_amortized_cleanup_reclaims_expired_on_put() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(10)
+            .ttl(Duration::from_millis(50))
+            .clean_interval(Duration::from_secs(3600))
+            .amortized_cleanup(2)
+            .build();
+        cache.stop_cleaner();
+        for i in 0..4 {
+            cache.insert(format!("old{i}"), "v".to_string());
+        }
+        sleep(Duration::from_millis(100)).await;
+
+        cache.put_with_ttl("new0".to_string(), "v".to_string(), Duration::from_secs(60));
+        assert_eq!(cache.len(), 3);
+        cache.put_with_ttl("new1".to_string(), "v".to_string(), Duration::from_secs(60));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().evictions.expired, 4);
+    }
+
+    #[tokio::test]
+    async fn test_update_many_mutates_in_place() {
+        let mut cache: Rustycache<String, u32> = RustycacheBuilder::new(STRATEGY).capacity(4).build();
+        cache.insert("a".to_string(), 1);
+        cache.insert("b".to_string(), 10);
+        cache.insert("c".to_string(), 100);
+
+        let keys = ["a".to_string(), "c".to_string(), "missing".to_string()];
+        cache.update_many(&keys, |_, value| *value += 1);
+
+        assert_eq!(cache.get(&"a".to_string()), Some(2));
+        assert_eq!(cache.get(&"b".to_string()), Some(10));
+        assert_eq!(cache.get(&"c".to_string()), Some(101));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_hasher_round_trips_integer_and_string_keys() {
+        // Runs against aHash when built with `--features fast-hash`.
+        let mut ints: Rustycache<u64, u64> = RustycacheBuilder::new(STRATEGY).capacity(1_000).build();
+        let mut strings: Rustycache<String, u64> = RustycacheBuilder::new(STRATEGY).capacity(1_000).deterministic(true).build();
+        for i in 0..1_000u64 {
+            ints.insert(i, i * 2);
+            strings.insert(format!("key{i}"), i);
+        }
+        for i in 0..1_000u64 {
+            assert_eq!(ints.get(&i), Some(i * 2));
+            assert_eq!(strings.get(&format!("key{i}")), Some(i));
+        }
+        assert_eq!(ints.get(&1_000), None);
+        assert!(ints.check_invariants().is_ok());
+        assert!(strings.check_invariants().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_promote_every_reorders_on_nth_access() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY).capacity(3).promote_every(3).build();
+        for key in ["a", "b", "c"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        assert_eq!(cache.debug_order(), vec!["a", "b", "c"]);
+
+        cache.get(&"a".to_string());
+        cache.get(&"a".to_string());
+        assert_eq!(cache.debug_order(), vec!["a", "b", "c"]);
+
+        cache.get(&"a".to_string());
+        assert_eq!(cache.debug_order(), vec!["b", "c", "a"]);
+
+        // The frequently read key still survives the next eviction.
+        cache.insert("d".to_string(), "d".to_string());
+        assert!(cache.contains(&"a".to_string()));
+        assert!(!cache.contains(&"b".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_clear_with_callbacks_visits_every_entry() {
+        use rustycache::strategy::CacheEvent;
+
+        let mut cache = create_cache(5, 60, 60);
+        let mut events = cache.subscribe();
+        for key in ["a", "b", "c"] {
+            cache.insert(key.to_string(), key.to_uppercase());
+        }
+
+        let mut seen = Vec::new();
+        cache.clear_with_callbacks(|key, value| seen.push((key, value)));
+        seen.sort();
+        assert_eq!(seen, vec![
+            ("a".to_string(), "A".to_string()),
+            ("b".to_string(), "B".to_string()),
+            ("c".to_string(), "C".to_string()),
+        ]);
+        assert!(cache.is_empty());
+        assert!(cache.check_invariants().is_ok());
+
+        let removed = std::iter::from_fn(|| events.try_recv().ok())
+            .filter(|event| matches!(event, CacheEvent::Removed(_)))
+            .count();
+        assert_eq!(removed, 3);
+    }
+
+    #[tokio::test]
+    async fn test_hydrate_from_copies_live_entries() {
+        use rustycache::rustycache::HydrateConflict;
+
+        let mut source = create_cache(10, 60, 60);
+        source.put_with_ttl("short".to_string(), "s".to_string(), Duration::from_secs(5));
+        source.put_with_ttl("long".to_string(), "l".to_string(), Duration::from_secs(600));
+        source.put_with_ttl("mid".to_string(), "m".to_string(), Duration::from_secs(60));
+
+        let mut target = create_cache(2, 60, 60);
+        assert_eq!(target.hydrate_from(&source), 3);
+        assert_eq!(target.len(), 2);
+        assert!(!target.contains(&"short".to_string()));
+        let mut meta = target.snapshot_with_meta();
+        meta.sort_by_key(|(_, _, remaining)| *remaining);
+        assert_eq!(meta[0].0, "mid");
+        assert!(meta[0].2 <= Duration::from_secs(60) && meta[0].2 > Duration::from_secs(55));
+        assert_eq!(meta[1].0, "long");
+        assert!(meta[1].2 > Duration::from_secs(590));
+
+        // "mid" outlives the local copy, "long" does not.
+        let mut peer = create_cache(10, 60, 60);
+        peer.put_with_ttl("mid".to_string(), "fresh".to_string(), Duration::from_secs(300));
+        peer.put_with_ttl("long".to_string(), "stale".to_string(), Duration::from_secs(10));
+        assert_eq!(target.hydrate_from(&peer), 1);
+        assert_eq!(target.get(&"mid".to_string()), Some("fresh".to_string()));
+        assert_eq!(target.get(&"long".to_string()), Some("l".to_string()));
+
+        assert_eq!(target.hydrate_from_with(&peer, HydrateConflict::KeepExisting), 0);
+        assert_eq!(target.hydrate_from_with(&peer, HydrateConflict::Overwrite), 2);
+        assert_eq!(target.get(&"long".to_string()), Some("stale".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_capacity_and_ttl_read_back() {
+        let cache = create_cache(7, 42, 60);
+        assert_eq!(cache.capacity(), 7);
+        assert_eq!(cache.ttl(), Duration::from_secs(42));
+    }
+
+    #[tokio::test]
+    async fn test_get_expect_returns_present_value() {
+        let mut cache = create_cache(2, 60, 60);
+        cache.insert("a".to_string(), "1".to_string());
+        assert_eq!(cache.get_expect(&"a".to_string()), "1");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "key not present in cache (missing or expired)")]
+    async fn test_get_expect_panics_on_missing_key() {
+        let mut cache = create_cache(2, 60, 60);
+        cache.get_expect(&"missing".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_expiry_index_stops_at_future_deadlines() {
+        use rustycache::strategy::lru::LRUCache;
+
+        let mut cache = create_cache(10, 60, 3600);
+        cache.put_with_ttl("a".to_string(), "1".to_string(), Duration::from_millis(20));
+        cache.put_with_ttl("b".to_string(), "2".to_string(), Duration::from_millis(40));
+        cache.put_with_ttl("c".to_string(), "3".to_string(), Duration::from_secs(10));
+        cache.put_with_ttl("d".to_string(), "4".to_string(), Duration::from_secs(20));
+        let buckets = |cache: &Rustycache<String, String>| cache.downcast_ref::<LRUCache<String, String>>().unwrap().expiry_buckets();
+        assert_eq!(buckets(&cache), 4);
+
+        sleep(Duration::from_millis(80)).await;
+        let mut drained: Vec<String> = cache.drain_expired().into_iter().map(|(key, _)| key).collect();
+        drained.sort();
+        assert_eq!(drained, vec!["a", "b"]);
+        // Only the two due buckets were popped; the future ones are untouched.
+        assert_eq!(buckets(&cache), 2);
+
+        // Refreshing a TTL moves the key to its new bucket.
+        assert!(cache.extend_ttl(&"c".to_string(), Duration::from_secs(5)));
+        assert_eq!(buckets(&cache), 2);
+        assert!(cache.check_invariants().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_try_new_rejects_invalid_arguments() {
+        use rustycache::rustycache::CacheConfigError;
+
+        let ok = Rustycache::<String, String>::try_new(10, Duration::from_secs(60), Duration::from_secs(1), STRATEGY);
+        assert!(ok.is_ok());
+
+        let zero_interval = Rustycache::<String, String>::try_new(10, Duration::from_secs(60), Duration::ZERO, STRATEGY);
+        assert_eq!(zero_interval.err(), Some(CacheConfigError::ZeroCleanInterval));
+
+        let huge = Duration::from_secs(u64::MAX);
+        let huge_ttl = Rustycache::<String, String>::try_new(10, huge, Duration::from_secs(1), STRATEGY);
+        assert_eq!(huge_ttl.err(), Some(CacheConfigError::TtlOutOfRange(huge)));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "invalid cache config: clean interval must be non-zero")]
+    async fn test_new_panics_on_zero_clean_interval() {
+        let _cache: Rustycache<String, String> = Rustycache::new(10, Duration::from_secs(60), Duration::ZERO, STRATEGY);
+    }
+
+    #[tokio::test]
+    async fn test_changed_wakes_on_put_of_key() {
+        let mut cache = create_cache(4, 60, 60);
+        let watcher = cache.clone();
+        let waiter = tokio::spawn(async move {
+            watcher.changed(&"k".to_string()).await;
+        });
+        sleep(Duration::from_millis(20)).await;
+
+        cache.insert("other".to_string(), "x".to_string());
+        sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        cache.insert("k".to_string(), "v".to_string());
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.expect("waiter woke").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_changed_wakes_on_update_many() {
+        let mut cache = create_cache(4, 60, 60);
+        cache.insert("k".to_string(), "v".to_string());
+        let watcher = cache.clone();
+        let waiter = tokio::spawn(async move {
+            watcher.changed(&"k".to_string()).await;
+        });
+        sleep(Duration::from_millis(20)).await;
+
+        cache.update_many(&["k".to_string()], |_, value| value.push('!'));
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.expect("waiter woke").unwrap();
+        assert_eq!(cache.get(&"k".to_string()), Some("v!".to_string()));
+        // An in-place update is not an insertion.
+        assert_eq!(cache.stats().insertions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_changed_sees_changes_before_first_poll() {
+        let mut cache = create_cache(4, 60, 60);
+        let changed = cache.changed(&"k".to_string());
+        cache.insert("k".to_string(), "v".to_string());
+        tokio::time::timeout(Duration::from_secs(1), changed).await.expect("change before the first poll was missed");
+    }
+
+    #[tokio::test]
+    async fn test_pinned_entries_survive_eviction() {
+        let mut cache = create_cache(3, 60, 60);
+        for key in ["a", "b", "c"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        assert!(cache.pin(&"a".to_string()));
+        assert!(!cache.pin(&"missing".to_string()));
+        assert_ne!(cache.peek_victim(), Some("a".to_string()));
+
+        for key in ["d", "e", "f"] {
+            cache.insert(key.to_string(), key.to_string());
+        }
+        assert_eq!(cache.len(), 3);
+        assert!(cache.contains(&"a".to_string()));
+        assert!(!cache.contains(&"b".to_string()));
+        assert!(!cache.contains(&"c".to_string()));
+        assert!(cache.check_invariants().is_ok());
+
+        // With every entry pinned, inserts grow the cache instead of evicting.
+        assert!(cache.pin(&"e".to_string()));
+        assert!(cache.pin(&"f".to_string()));
+        cache.insert("g".to_string(), "g".to_string());
+        assert_eq!(cache.len(), 4);
+        assert!(cache.check_invariants().is_ok());
+
+        cache.unpin(&"a".to_string());
+        cache.insert("h".to_string(), "h".to_string());
+        assert_eq!(cache.len(), 3);
+        assert!(!cache.contains(&"a".to_string()) && !cache.contains(&"g".to_string()));
+        assert!(cache.contains(&"e".to_string()) && cache.contains(&"f".to_string()));
+        assert!(cache.check_invariants().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_compact_rebuilds_structures_after_churn() {
+        use rustycache::strategy::lru::LRUCache;
+
+        let mut cache = create_cache(2_000, 60, 3600);
+        for i in 0..2_000 {
+            cache.put_with_ttl(format!("k{i}"), "v".to_string(), Duration::from_secs(60 + i));
+        }
+        for i in 0..1_990 {
+            cache.remove(&format!("k{i}"));
+        }
+        let before = cache.allocated_capacity();
+        let buckets = |cache: &Rustycache<String, String>| cache.downcast_ref::<LRUCache<String, String>>().unwrap().expiry_buckets();
+        assert_eq!(buckets(&cache), 10);
+
+        cache.compact();
+        assert!(cache.check_invariants().is_ok());
+        assert!(cache.allocated_capacity() < before);
+        assert_eq!(buckets(&cache), 10);
+        assert_eq!(cache.len(), 10);
+        assert_eq!(cache.get(&"k1995".to_string()), Some("v".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_load_with_caches_with_loader_ttl() {
+        let cache = create_cache(4, 60, 60);
+        let loaded: Result<String, String> = cache
+            .load_with("k".to_string(), |key| {
+                let value = format!("{key}-loaded");
+                async move { Ok((value, Duration::from_secs(5))) }
+            })
+            .await;
+        assert_eq!(loaded, Ok("k-loaded".to_string()));
+        let remaining = cache.remaining_ttl(&"k".to_string()).unwrap();
+        assert!(remaining <= Duration::from_secs(5) && remaining > Duration::from_secs(4));
+
+        // A hit never calls the loader.
+        let hit: Result<String, String> = cache
+            .load_with("k".to_string(), |_| async { panic!("loader called on a hit") })
+            .await;
+        assert_eq!(hit, Ok("k-loaded".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_load_with_error_caches_nothing() {
+        let cache = create_cache(4, 60, 60);
+        let failed: Result<String, String> = cache
+            .load_with("k".to_string(), |_| async { Err("upstream down".to_string()) })
+            .await;
+        assert_eq!(failed, Err("upstream down".to_string()));
+        assert!(!cache.contains(&"k".to_string()));
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_clear_is_atomic_for_concurrent_observers() {
+        let cache = create_cache(64, 30, 60);
+
+        let mut churner = cache.clone();
+        let handle = std::thread::spawn(move || {
+            for round in 0..2_000 {
+                for i in 0..16 {
+                    churner.insert(format!("k{i}"), round.to_string());
+                }
+                churner.clear();
+            }
+        });
+
+        while !handle.is_finished() {
+            if let Err(message) = cache.check_invariants() {
+                panic!("observed a partially cleared cache: {}", message);
+            }
+            let order = cache.debug_order();
+            assert!(order.len() <= 16);
+        }
+        handle.join().unwrap();
+        assert!(cache.is_empty());
+        assert!(cache.debug_order().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_without_cleaner_is_swept_by_evict_expired() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(10)
+            .ttl(Duration::from_millis(50))
+            .without_cleaner()
+            .build();
+        for i in 0..3 {
+            cache.insert(i.to_string(), i.to_string());
+        }
+        sleep(Duration::from_millis(120)).await;
+        assert_eq!(cache.len(), 3);
+
+        assert_eq!(cache.evict_expired(), 3);
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.evict_expired(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_key_normalizer_applies_to_every_lookup() {
+        let mut cache = create_cache(10, 30, 60).with_key_normalizer(|key: &String| key.to_lowercase());
+
+        cache.insert("abc".to_string(), "value".to_string());
+        assert_eq!(cache.get(&"ABC".to_string()), Some("value".to_string()));
+        assert!(cache.contains(&"aBc".to_string()));
+
+        cache.insert("ABC".to_string(), "updated".to_string());
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.remove(&"Abc".to_string()), Some("updated".to_string()));
+        assert!(cache.is_empty());
+    }
+
+    #[cfg(not(feature = "parking-lot"))]
+    #[tokio::test]
+    async fn test_is_healthy_detects_poisoned_lock() {
+        let mut cache = create_cache(10, 30, 60);
+        cache.insert("k".to_string(), "v".to_string());
+        assert!(cache.is_healthy());
+
+        let mut poisoner = cache.clone();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            poisoner.update_many(&["k".to_string()], |_, _| panic!("boom"));
+        }));
+        assert!(result.is_err());
+        assert!(!cache.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_eviction_filter_veto_redirects_to_next_victim() {
+        let mut cache = create_cache(2, 30, 60);
+        cache.set_eviction_filter(|key: &String, _: &String| key != "a");
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        assert_eq!(cache.peek_victim(), Some("b".to_string()));
+
+        cache.insert("c".to_string(), "3".to_string());
+        assert!(cache.contains(&"a".to_string()));
+        assert!(!cache.contains(&"b".to_string()));
+        assert!(cache.contains(&"c".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_eviction_filter_vetoing_everything_falls_back_to_usual_victim() {
+        let mut cache = create_cache(2, 30, 60);
+        cache.set_eviction_filter(|_: &String, _: &String| false);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+        cache.insert("c".to_string(), "3".to_string());
+
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains(&"a".to_string()));
+        assert!(cache.check_invariants().is_ok());
+
+        cache.clear_eviction_filter();
+        cache.insert("d".to_string(), "4".to_string());
+        assert!(!cache.contains(&"b".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_get_with_metadata_has_no_frequency_outside_lfu() {
+        let mut cache = create_cache(10, 30, 60);
+        cache.insert("k".to_string(), "v".to_string());
+        let item = cache.get_with_metadata(&"k".to_string()).unwrap();
+        assert_eq!(item.value, "v");
+        assert_eq!(item.frequency, None);
+        assert!(item.expires_at.is_some());
+        assert!(item.remaining_ttl <= Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_set_ttl_only_affects_later_inserts() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(10)
+            .ttl(Duration::from_millis(200))
+            .clean_interval(Duration::from_secs(60))
+            .build();
+        cache.insert("old".to_string(), "1".to_string());
+
+        cache.set_ttl(Duration::from_secs(30));
+        assert_eq!(cache.ttl(), Duration::from_secs(30));
+        cache.insert("new".to_string(), "2".to_string());
+        assert!(cache.remaining_ttl(&"old".to_string()).unwrap() <= Duration::from_millis(200));
+
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(cache.get(&"old".to_string()), None);
+        assert_eq!(cache.get(&"new".to_string()), Some("2".to_string()));
+        assert!(cache.remaining_ttl(&"new".to_string()).unwrap() > Duration::from_secs(29));
+    }
+
+    #[tokio::test]
+    async fn test_contains_and_refresh_extends_life_without_cloning() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct Counted(Arc<AtomicUsize>);
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Counted(Arc::clone(&self.0))
+            }
+        }
+
+        let clones = Arc::new(AtomicUsize::new(0));
+        let mut cache: Rustycache<String, Counted> = RustycacheBuilder::new(STRATEGY)
+            .capacity(2)
+            .ttl(Duration::from_millis(300))
+            .clean_interval(Duration::from_secs(60))
+            .build();
+        cache.insert("a".to_string(), Counted(Arc::clone(&clones)));
+        cache.insert("b".to_string(), Counted(Arc::clone(&clones)));
+
+        sleep(Duration::from_millis(200)).await;
+        assert!(cache.contains_and_refresh(&"a".to_string()));
+        assert!(!cache.contains_and_refresh(&"missing".to_string()));
+        assert_eq!(clones.load(Ordering::SeqCst), 0);
+        assert_eq!(cache.peek_victim(), Some("b".to_string()));
+
+        sleep(Duration::from_millis(200)).await;
+        assert!(cache.remaining_ttl(&"a".to_string()).is_some());
+        assert!(cache.remaining_ttl(&"b".to_string()).is_none());
+        assert!(!cache.contains_and_refresh(&"b".to_string()));
+        assert!(!cache.contains(&"b".to_string()));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_try_clear_gives_up_while_lock_is_held() {
+        use std::sync::mpsc;
+        use std::time::Instant;
+
+        let mut cache = create_cache(10, 30, 60);
+        cache.insert("k".to_string(), "v".to_string());
+
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let mut holder = cache.clone();
+        let handle = std::thread::spawn(move || {
+            holder.update_many(&["k".to_string()], |_, _| {
+                locked_tx.send(()).unwrap();
+                std::thread::sleep(Duration::from_millis(300));
+            });
+        });
+        locked_rx.recv().unwrap();
+
+        let started = Instant::now();
+        assert!(!cache.try_clear());
+        assert!(started.elapsed() < Duration::from_millis(100));
+        handle.join().unwrap();
+
+        assert_eq!(cache.len(), 1);
+        assert!(cache.try_clear());
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_freeze_suspends_expiry_and_eviction() {
+        let mut cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY)
+            .capacity(2)
+            .ttl(Duration::from_millis(50))
+            .clean_interval(Duration::from_millis(20))
+            .build();
+        cache.insert("a".to_string(), "1".to_string());
+        cache.insert("b".to_string(), "2".to_string());
+
+        cache.freeze();
+        sleep(Duration::from_millis(120)).await;
+        assert_eq!(cache.get(&"a".to_string()), Some("1".to_string()));
+        assert_eq!(cache.get(&"b".to_string()), Some("2".to_string()));
+        assert_eq!(cache.evict_expired(), 0);
+        cache.insert("c".to_string(), "3".to_string());
+        assert_eq!(cache.len(), 3);
+        assert!(cache.check_invariants().is_ok());
+
+        cache.unfreeze();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.check_invariants().is_ok());
+        assert_eq!(cache.get(&"a".to_string()), None);
+        sleep(Duration::from_millis(120)).await;
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unfreeze_keeps_entries_under_high_watermark() {
+        let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY).watermarks(5, 10).build();
+        for i in 0..8 {
+            cache.insert(i, i);
+        }
+
+        cache.freeze();
+        cache.insert(8, 8);
+        cache.insert(9, 9);
+        cache.insert(10, 10);
+        assert_eq!(cache.len(), 11);
+
+        cache.unfreeze();
+        assert_eq!(cache.len(), 10);
+        assert!(cache.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_frozen_cache_refreshes_pins_and_extends_past_deadline() {
+        use rustycache::strategy::MockClock;
+
+        let clock = MockClock::new(chrono::Utc::now());
+        let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY)
+            .ttl(Duration::from_secs(10))
+            .without_cleaner()
+            .clock(clock.clone())
+            .build();
+        for i in 0..3 {
+            cache.insert(i, i);
+        }
+
+        cache.freeze();
+        clock.advance_time(Duration::from_secs(20));
+        assert!(cache.contains_and_refresh(&0));
+        assert!(cache.pin(&1));
+        assert!(cache.extend_ttl(&2, Duration::from_secs(30)));
+        assert_eq!(cache.len(), 3);
+
+        cache.unfreeze();
+        assert_eq!(cache.get(&0), Some(0));
+        assert_eq!(cache.get(&2), Some(2));
+    }
+
+    fn frozen_past_deadline() -> (Rustycache<u32, u32>, rustycache::strategy::MockClock) {
+        let clock = rustycache::strategy::MockClock::new(chrono::Utc::now());
+        let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY)
+            .ttl(Duration::from_secs(10))
+            .without_cleaner()
+            .clock(clock.clone())
+            .build();
+        cache.insert(1, 1);
+        clock.advance_time(Duration::from_secs(4));
+        cache.freeze();
+        clock.advance_time(Duration::from_secs(20));
+        (cache, clock)
+    }
+
+    #[test]
+    fn test_frozen_update_many_reaches_entries_past_deadline() {
+        let (mut cache, _clock) = frozen_past_deadline();
+        cache.update_many(&[1], |_, value| *value += 1);
+        assert_eq!(cache.get(&1), Some(2));
+    }
+
+    #[test]
+    fn test_frozen_take_returns_entry_past_deadline() {
+        let (mut cache, _clock) = frozen_past_deadline();
+        assert_eq!(cache.take(&1), Some(1));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_frozen_snapshot_with_meta_keeps_ttl_left_at_freeze() {
+        let (cache, _clock) = frozen_past_deadline();
+        assert_eq!(cache.snapshot_with_meta(), vec![(1, 1, Duration::from_secs(6))]);
+    }
+
+    #[test]
+    fn test_frozen_remaining_ttl_stops_at_freeze() {
+        let (cache, _clock) = frozen_past_deadline();
+        assert_eq!(cache.remaining_ttl(&1), Some(Duration::from_secs(6)));
+    }
+
+    #[test]
+    fn test_frozen_count_by_sees_entries_past_deadline() {
+        let (cache, _clock) = frozen_past_deadline();
+        assert_eq!(cache.count_by(|key| key % 2), [(1, 1)].into_iter().collect());
+        assert_eq!(cache.report().live_len, 1);
+    }
+
+    #[test]
+    fn test_cleaner_time_budget_defers_backlog_to_next_tick() {
+        use rustycache::strategy::MockClock;
+
+        let clock = MockClock::new(chrono::Utc::now());
+        let cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY)
+            .capacity(100)
+            .ttl(Duration::from_secs(1))
+            .without_cleaner()
+            .clock(clock.clone())
+            .cleaner_batch(10)
+            .cleaner_time_budget(Duration::ZERO)
+            .build();
+        let mut writer = cache.clone();
+        for i in 0..50 {
+            writer.insert(i, i);
+        }
+        clock.advance_time(Duration::from_secs(2));
+
+        // A zero budget allows exactly one batch per tick.
+        cache.step_cleaner();
+        assert_eq!(cache.len(), 40);
+        cache.step_cleaner();
+        assert_eq!(cache.len(), 30);
+
+        for _ in 0..3 {
+            cache.step_cleaner();
+        }
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_group_promotes_only_last_key() {
+        use rustycache::strategy::PromotePolicy;
+
+        let mut cache = create_cache(10, 30, 60);
+        for key in ["a", "b", "c", "d"] {
+            cache.insert(key.to_string(), key.to_uppercase());
+        }
+        let group = vec!["a".to_string(), "b".to_string(), "missing".to_string()];
+
+        let values = cache.get_group(&group[..2], PromotePolicy::Last);
+        assert_eq!(values, vec![Some("A".to_string()), Some("B".to_string())]);
+        assert_eq!(cache.debug_order(), vec!["a", "c", "d", "b"]);
+
+        assert_eq!(cache.get_group(&group, PromotePolicy::None), vec![Some("A".to_string()), Some("B".to_string()), None]);
+        assert_eq!(cache.debug_order(), vec!["a", "c", "d", "b"]);
+
+        cache.get_group(&group[..2], PromotePolicy::All);
+        assert_eq!(cache.debug_order(), vec!["c", "d", "a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_recent_evictions_records_reasons_and_stays_bounded() {
+        use rustycache::strategy::{EvictReason, RECENT_EVICTIONS_CAPACITY};
+
+        let mut cache = create_cache(2, 1, 60);
+        let start = chrono::Utc::now();
+        cache.insert("a".to_string(), "A".to_string());
+        cache.insert("b".to_string(), "B".to_string());
+        cache.insert("c".to_string(), "C".to_string());
+        cache.remove(&"c".to_string());
+
+        sleep(Duration::from_millis(1100)).await;
+        let before_expiry = chrono::Utc::now();
+        assert_eq!(cache.get(&"b".to_string()), None);
+
+        let log: Vec<_> = cache.recent_evictions().into_iter().map(|(key, _, reason)| (key, reason)).collect();
+        assert_eq!(log, vec![("a".to_string(), EvictReason::Capacity), ("b".to_string(), EvictReason::Expired)]);
+        assert!(cache.recent_evictions().iter().all(|(_, at, _)| *at >= start));
+        let since: Vec<_> = cache.evicted_since(before_expiry).into_iter().map(|(key, _, _)| key).collect();
+        assert_eq!(since, vec!["b".to_string()]);
+
+        let mut small = create_cache(1, 60, 60);
+        for i in 0..=RECENT_EVICTIONS_CAPACITY + 10 {
+            small.insert(i.to_string(), i.to_string());
+        }
+        let log = small.recent_evictions();
+        assert_eq!(log.len(), RECENT_EVICTIONS_CAPACITY);
+        assert_eq!(log[0].0, "10");
+        assert_eq!(log.last().unwrap().0, (RECENT_EVICTIONS_CAPACITY + 9).to_string());
+    }
+
+    #[tokio::test]
+    async fn test_put_coalescing_applies_only_last_value() {
+        let mut cache = create_cache(10, 60, 60).with_put_coalescing(Duration::from_millis(100));
+        cache.insert("k".to_string(), "old".to_string());
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(cache.stats().insertions, 1);
+
+        for i in 0..20 {
+            cache.insert("k".to_string(), format!("v{i}"));
+        }
+        cache.insert("gone".to_string(), "x".to_string());
+        cache.remove(&"gone".to_string());
+        assert_eq!(cache.get(&"k".to_string()), Some("old".to_string()));
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(cache.stats().insertions, 2);
+        assert_eq!(cache.get(&"k".to_string()), Some("v19".to_string()));
+        assert!(!cache.contains(&"gone".to_string()));
+
+        cache.insert("k".to_string(), "flushed".to_string());
+        cache.flush().await;
+        assert_eq!(cache.get(&"k".to_string()), Some("flushed".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_failed_try_clear_keeps_coalesced_puts() {
+        let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY).build().with_put_coalescing(Duration::from_millis(50));
+        cache.insert(1, 1);
+        cache.flush().await;
+        cache.insert(2, 2);
+
+        let mut other = cache.clone();
+        let mut cleared = true;
+        cache.update_many(&[1], |_, _| cleared = other.try_clear());
+        assert!(!cleared);
+
+        cache.flush().await;
+        assert_eq!(cache.get(&2), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_get_arc_shares_one_allocation() {
+        use std::sync::Arc;
+        use rustycache::SharedRustycache;
+
+        let mut cache: SharedRustycache<String, Vec<u8>> = Rustycache::new(10, Duration::from_secs(60), Duration::from_secs(60), STRATEGY);
+        cache.insert_shared("blob".to_string(), vec![7; 1 << 20]);
+
+        let first = cache.get_arc(&"blob".to_string()).unwrap();
+        let second = cache.get_arc(&"blob".to_string()).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.len(), 1 << 20);
+        assert_eq!(cache.get_arc(&"missing".to_string()), None);
+    }
+
+    #[test]
+    fn test_mock_clock_drives_expiry_without_runtime() {
+        use rustycache::strategy::MockClock;
+
+        let clock = MockClock::new(chrono::Utc::now());
+        let mut cache: Rustycache<String, u32> = RustycacheBuilder::new(STRATEGY)
+            .ttl(Duration::from_secs(10))
+            .without_cleaner()
+            .clock(clock.clone())
+            .build();
+        cache.insert("default".to_string(), 1);
+        cache.put_with_ttl("short".to_string(), 2, Duration::from_secs(3));
+
+        clock.advance_time(Duration::from_secs(2));
+        cache.step_cleaner();
+        assert_eq!(cache.len(), 2);
+
+        clock.advance_time(Duration::from_secs(1));
+        cache.step_cleaner();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&"short".to_string()), None);
+
+        clock.advance_time(Duration::from_secs(6));
+        assert_eq!(cache.get(&"default".to_string()), Some(1));
+        clock.advance_time(Duration::from_secs(1));
+        cache.step_cleaner();
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_with_reads_non_clone_value() {
+        struct Connection {
+            id: u32,
+        }
+
+        let mut cache: Rustycache<String, Connection> = RustycacheBuilder::new(STRATEGY).capacity(4).build();
+        cache.insert("db".to_string(), Connection { id: 7 });
+
+        assert_eq!(cache.get_with(&"db".to_string(), |conn| conn.id), Some(7));
+        assert_eq!(cache.get_with(&"missing".to_string(), |conn| conn.id), None);
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_but_present_count_tracks_uncleaned_entries() {
+        let mut cache = create_cache(10, 60, 3600);
+        cache.put_with_ttl("a".to_string(), "1".to_string(), Duration::from_millis(50));
+        cache.put_with_ttl("b".to_string(), "2".to_string(), Duration::from_millis(50));
+        cache.insert("c".to_string(), "3".to_string());
+        assert_eq!(cache.expired_but_present_count(), 0);
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(cache.expired_but_present_count(), 2);
+        assert_eq!(cache.len(), 3);
+
+        cache.evict_expired();
+        assert_eq!(cache.expired_but_present_count(), 0);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sliding_refresh_with_huge_ttl_makes_entry_permanent() {
+        let mut cache = create_cache(10, 60, 60);
+        cache.set_expiration_policy(rustycache::strategy::ExpirationPolicy::Sliding);
+        cache.insert("a".to_string(), "1".to_string());
+        cache.set_ttl(Duration::MAX);
+
+        assert_eq!(cache.get(&"a".to_string()), Some("1".to_string()));
+        assert_eq!(cache.remaining_ttl(&"a".to_string()), Some(Duration::MAX));
+        assert!(cache.check_invariants().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_put_await_on_zero_capacity_returns() {
+        let cache: Rustycache<String, String> = RustycacheBuilder::new(STRATEGY).capacity(0).build();
+        tokio::time::timeout(Duration::from_millis(100), cache.put_await("a".to_string(), "1".to_string()))
+            .await
+            .expect("put_await hung on a zero-capacity cache");
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_deterministic_expiry_order_is_reproducible() {
+        use rustycache::strategy::MockClock;
+
+        let start = chrono::Utc::now();
+        let drained = || {
+            let clock = MockClock::new(start);
+            let mut cache: Rustycache<u32, u32> = RustycacheBuilder::new(STRATEGY)
+                .deterministic(true)
+                .without_cleaner()
+                .clock(clock.clone())
+                .build();
+            for i in 0..20 {
+                cache.insert(i, i);
+            }
+            clock.advance_time(Duration::from_secs(120));
+            cache.drain_expired().into_iter().map(|(key, _)| key).collect::<Vec<_>>()
+        };
+
+        let first = drained();
+        assert_eq!(first.len(), 20);
+        assert_eq!(first, drained());
+    }
+
+    #[tokio::test]
+    async fn test_permanent_entries_report_max_remaining_ttl() {
+        let mut cache = create_cache(10, 60, 1);
+        cache.put_permanent("forever".to_string(), "1".to_string());
+        cache.insert("brief".to_string(), "2".to_string());
+
+        let remaining: std::collections::HashMap<String, Duration> = cache
+            .snapshot_with_meta()
+            .into_iter()
+            .map(|(key, _, remaining)| (key, remaining))
+            .collect();
+        assert_eq!(remaining["forever"], Duration::MAX);
+        assert!(remaining["brief"] <= Duration::from_secs(60));
+
+        let mapped = cache.map_values(|value| value.len());
+        assert_eq!(mapped.remaining_ttl(&"forever".to_string()), Some(Duration::MAX));
+
+        let mut hydrated = create_cache(10, 60, 1);
+        hydrated.hydrate_from(&cache);
+        assert_eq!(hydrated.remaining_ttl(&"forever".to_string()), Some(Duration::MAX));
+        assert!(hydrated.remaining_ttl(&"brief".to_string()) <= Some(Duration::from_secs(60)));
+    }
+
+    #[tokio::test]
+    async fn test_expiry_index_does_not_outgrow_entries_under_churn() {
+        use rustycache::strategy::lru::LRUCache;
+
+        let mut cache = create_cache(10, 60, 3600);
+        let buckets = |cache: &Rustycache<String, String>| cache.downcast_ref::<LRUCache<String, String>>().unwrap().expiry_buckets();
+
+        // Evictions.
+        for i in 0..1_000u64 {
+            cache.put_with_ttl(format!("k{i}"), "v".to_string(), Duration::from_secs(60 + i));
+        }
+        assert!(buckets(&cache) <= cache.len());
+
+        // Overwrites of a single key.
+        for i in 0..1_000u64 {
+            cache.put_with_ttl("hot".to_string(), "v".to_string(), Duration::from_secs(2_000 + i));
+        }
+        assert!(buckets(&cache) <= cache.len());
+
+        // Explicit removals.
+        cache.remove(&"k999".to_string());
+        cache.take(&"k998".to_string());
+        cache.remove_many(&["k997".to_string(), "k996".to_string()]);
+        assert!(buckets(&cache) <= cache.len());
+        assert!(cache.check_invariants().is_ok());
+    }
 }
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod macro_tests {
+    use std::time::Duration;
+    use rustycache::cache;
+    use rustycache::rustycache::{Rustycache, RustycacheBuilder};
+    use rustycache::strategy::StrategyType;
+
+    #[tokio::test]
+    async fn test_cache_macro_matches_manual_construction() {
+        let mut from_macro = cache!(lru, cap = 100, ttl = 5s, { "a" => 1, "b" => 2 });
+
+        let mut manual: Rustycache<&str, i32> = RustycacheBuilder::new(StrategyType::LRU)
+            .capacity(100)
+            .ttl(Duration::from_secs(5))
+            .build();
+        manual.insert("a", 1);
+        manual.insert("b", 2);
+
+        assert_eq!(from_macro.report().strategy_name, manual.report().strategy_name);
+        assert_eq!(from_macro.capacity(), manual.capacity());
+        assert_eq!(from_macro.ttl(), manual.ttl());
+        assert_eq!(from_macro.debug_order(), manual.debug_order());
+        assert_eq!(from_macro.get(&"a"), manual.get(&"a"));
+        assert_eq!(from_macro.get(&"b"), manual.get(&"b"));
+    }
+
+    #[tokio::test]
+    async fn test_cache_macro_empty_body_and_defaults() {
+        let empty: Rustycache<String, u32> = cache!(fifo, cap = 3, ttl = 250ms, {});
+        assert!(empty.is_empty());
+        assert_eq!(empty.capacity(), 3);
+        assert_eq!(empty.ttl(), Duration::from_millis(250));
+
+        let bare: Rustycache<String, u32> = cache!(lfu);
+        let defaults: Rustycache<String, u32> = RustycacheBuilder::new(StrategyType::LFU).build();
+        assert_eq!(bare.capacity(), defaults.capacity());
+        assert_eq!(bare.ttl(), defaults.ttl());
+        assert_eq!(bare.report().strategy_name, "LFU");
+    }
+
+    #[tokio::test]
+    async fn test_cache_macro_respects_capacity_and_units() {
+        let mut cache = cache!(fifo, ttl = 2m, cap = 2, interval = 1h, {
+            "a".to_string() => 1,
+            "b".to_string() => 2,
+            "c".to_string() => 3,
+        });
+        assert_eq!(cache.ttl(), Duration::from_secs(120));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"a".to_string()), None);
+        assert_eq!(cache.get(&"c".to_string()), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_try_build_rejects_invalid_config() {
+        use rustycache::rustycache::CacheConfigError;
+
+        let zero_interval = RustycacheBuilder::new(StrategyType::LRU)
+            .clean_interval(Duration::ZERO)
+            .try_build::<String, u32>();
+        assert_eq!(zero_interval.err(), Some(CacheConfigError::ZeroCleanInterval));
+
+        let huge_ttl = RustycacheBuilder::new(StrategyType::FIFO)
+            .ttl(Duration::MAX)
+            .try_build::<String, u32>();
+        assert_eq!(huge_ttl.err(), Some(CacheConfigError::TtlOutOfRange(Duration::MAX)));
+
+        let ok = RustycacheBuilder::new(StrategyType::LFU).try_build::<String, u32>();
+        assert!(ok.is_ok());
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "invalid cache config: clean interval must be non-zero")]
+    async fn test_cache_macro_panics_on_zero_interval() {
+        let _cache: Rustycache<String, u32> = cache!(lru, interval = 0ms);
+    }
+}
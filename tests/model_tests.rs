@@ -0,0 +1,285 @@
+#[cfg(test)]
+mod model_tests {
+    use std::collections::VecDeque;
+    use std::time::Duration;
+    use proptest::prelude::*;
+    use rustycache::rustycache::Rustycache;
+    use rustycache::strategy::StrategyType;
+
+    #[derive(Clone, Debug)]
+    enum Op {
+        Put(u8, u32),
+        Get(u8),
+        Remove(u8),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            3 => (0u8..8, any::<u32>()).prop_map(|(key, value)| Op::Put(key, value)),
+            3 => (0u8..8).prop_map(Op::Get),
+            1 => (0u8..8).prop_map(Op::Remove),
+        ]
+    }
+
+    /// Reference behaviour the real strategies are checked against. TTLs are
+    /// long enough to never fire, so only capacity eviction is modelled.
+    trait Model {
+        fn put(&mut self, key: u8, value: u32);
+        fn get(&mut self, key: u8) -> Option<u32>;
+        fn remove(&mut self, key: u8);
+        fn len(&self) -> usize;
+    }
+
+    /// Most recently used at the front.
+    struct LruModel {
+        capacity: usize,
+        entries: VecDeque<(u8, u32)>,
+    }
+
+    impl Model for LruModel {
+        fn put(&mut self, key: u8, value: u32) {
+            if let Some(pos) = self.entries.iter().position(|(k, _)| *k == key) {
+                self.entries.remove(pos);
+            } else if self.entries.len() >= self.capacity {
+                self.entries.pop_back();
+            }
+            self.entries.push_front((key, value));
+        }
+
+        fn get(&mut self, key: u8) -> Option<u32> {
+            let pos = self.entries.iter().position(|(k, _)| *k == key)?;
+            let entry = self.entries.remove(pos)?;
+            self.entries.push_front(entry);
+            Some(entry.1)
+        }
+
+        fn remove(&mut self, key: u8) {
+            self.entries.retain(|(k, _)| *k != key);
+        }
+
+        fn len(&self) -> usize {
+            self.entries.len()
+        }
+    }
+
+    /// Oldest insertion at the front; existing keys are never overwritten.
+    struct FifoModel {
+        capacity: usize,
+        entries: VecDeque<(u8, u32)>,
+    }
+
+    impl Model for FifoModel {
+        fn put(&mut self, key: u8, value: u32) {
+            if self.entries.iter().any(|(k, _)| *k == key) {
+                return;
+            }
+            if self.entries.len() >= self.capacity {
+                self.entries.pop_front();
+            }
+            self.entries.push_back((key, value));
+        }
+
+        fn get(&mut self, key: u8) -> Option<u32> {
+            self.entries.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+        }
+
+        fn remove(&mut self, key: u8) {
+            self.entries.retain(|(k, _)| *k != key);
+        }
+
+        fn len(&self) -> usize {
+            self.entries.len()
+        }
+    }
+
+    /// Evicts the lowest frequency; ties go to the key that reached that
+    /// frequency first.
+    struct LfuModel {
+        capacity: usize,
+        clock: u64,
+        entries: Vec<(u8, u32, usize, u64)>,
+    }
+
+    impl LfuModel {
+        fn tick(&mut self) -> u64 {
+            self.clock += 1;
+            self.clock
+        }
+    }
+
+    impl Model for LfuModel {
+        fn put(&mut self, key: u8, value: u32) {
+            if let Some(entry) = self.entries.iter_mut().find(|e| e.0 == key) {
+                entry.1 = value;
+                return;
+            }
+            if self.entries.len() >= self.capacity {
+                let victim = self.entries.iter()
+                    .enumerate()
+                    .min_by_key(|(_, e)| (e.2, e.3))
+                    .map(|(i, _)| i);
+                if let Some(i) = victim {
+                    self.entries.remove(i);
+                }
+            }
+            let seq = self.tick();
+            self.entries.push((key, value, 1, seq));
+        }
+
+        fn get(&mut self, key: u8) -> Option<u32> {
+            let seq = self.tick();
+            let entry = self.entries.iter_mut().find(|e| e.0 == key)?;
+            entry.2 += 1;
+            entry.3 = seq;
+            Some(entry.1)
+        }
+
+        fn remove(&mut self, key: u8) {
+            self.entries.retain(|e| e.0 != key);
+        }
+
+        fn len(&self) -> usize {
+            self.entries.len()
+        }
+    }
+
+    fn run(strategy: StrategyType, capacity: usize, ops: &[Op], model: &mut dyn Model) -> Result<(), TestCaseError> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let _guard = runtime.enter();
+        let mut cache: Rustycache<u8, u32> =
+            Rustycache::new(capacity, Duration::from_secs(600), Duration::from_secs(600), strategy);
+
+        for op in ops {
+            match *op {
+                Op::Put(key, value) => {
+                    cache.insert(key, value);
+                    model.put(key, value);
+                }
+                Op::Get(key) => prop_assert_eq!(cache.get(&key), model.get(key), "get({})", key),
+                Op::Remove(key) => {
+                    cache.remove(&key);
+                    model.remove(key);
+                }
+            }
+            prop_assert_eq!(cache.len(), model.len());
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn lru_matches_model(capacity in 1usize..6, ops in prop::collection::vec(op(), 1..200)) {
+            let mut model = LruModel { capacity, entries: VecDeque::new() };
+            run(StrategyType::LRU, capacity, &ops, &mut model)?;
+        }
+
+        #[test]
+        fn fifo_matches_model(capacity in 1usize..6, ops in prop::collection::vec(op(), 1..200)) {
+            let mut model = FifoModel { capacity, entries: VecDeque::new() };
+            run(StrategyType::FIFO, capacity, &ops, &mut model)?;
+        }
+
+        #[test]
+        fn lfu_matches_model(capacity in 1usize..6, ops in prop::collection::vec(op(), 1..200)) {
+            let mut model = LfuModel { capacity, clock: 0, entries: Vec::new() };
+            run(StrategyType::LFU, capacity, &ops, &mut model)?;
+        }
+    }
+
+    #[test]
+    fn lfu_tie_break_prefers_least_recently_promoted() {
+        let ops = [
+            Op::Put(1, 1),
+            Op::Put(2, 2),
+            Op::Get(2),
+            Op::Get(1),
+            Op::Put(3, 3),
+            Op::Get(1),
+            Op::Get(2),
+        ];
+        let mut model = LfuModel { capacity: 2, clock: 0, entries: Vec::new() };
+        run(StrategyType::LFU, 2, &ops, &mut model).unwrap();
+    }
+
+    /// Operations for the LFU bookkeeping check. Unlike `Op`, these include
+    /// expiry and the bulk paths, since only internal consistency is checked.
+    #[derive(Clone, Debug)]
+    enum LfuOp {
+        Put(u8),
+        PutExpired(u8),
+        Get(u8),
+        Remove(u8),
+        UpdateMany(Vec<u8>),
+        DrainExpired,
+        PruneTo(usize),
+    }
+
+    fn lfu_op() -> impl Strategy<Value = LfuOp> {
+        prop_oneof![
+            4 => (0u8..8).prop_map(LfuOp::Put),
+            1 => (0u8..8).prop_map(LfuOp::PutExpired),
+            4 => (0u8..8).prop_map(LfuOp::Get),
+            1 => (0u8..8).prop_map(LfuOp::Remove),
+            1 => prop::collection::vec(0u8..8, 0..4).prop_map(LfuOp::UpdateMany),
+            1 => Just(LfuOp::DrainExpired),
+            1 => (0usize..4).prop_map(LfuOp::PruneTo),
+        ]
+    }
+
+    /// Checks after every operation that each key in the LFU map sits in
+    /// exactly one frequency bucket at its recorded frequency, and back.
+    fn run_lfu_bookkeeping(capacity: usize, ops: &[LfuOp]) -> Result<(), TestCaseError> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let _guard = runtime.enter();
+        let mut cache: Rustycache<u8, u32> =
+            Rustycache::new(capacity, Duration::from_secs(600), Duration::from_secs(600), StrategyType::LFU);
+
+        for op in ops {
+            match op {
+                LfuOp::Put(key) => cache.insert(*key, 0),
+                LfuOp::PutExpired(key) => cache.put_with_ttl(*key, 0, Duration::ZERO),
+                LfuOp::Get(key) => {
+                    cache.get(key);
+                }
+                LfuOp::Remove(key) => {
+                    cache.remove(key);
+                }
+                LfuOp::UpdateMany(keys) => cache.update_many(keys, |_, value| *value += 1),
+                LfuOp::DrainExpired => {
+                    cache.drain_expired();
+                }
+                LfuOp::PruneTo(target) => {
+                    cache.prune_to(*target);
+                }
+            }
+            if let Err(message) = cache.check_invariants() {
+                return Err(TestCaseError::fail(format!("after {:?}: {}", op, message)));
+            }
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn lfu_bookkeeping_stays_consistent(capacity in 1usize..6, ops in prop::collection::vec(lfu_op(), 1..300)) {
+            run_lfu_bookkeeping(capacity, &ops)?;
+        }
+    }
+
+    #[test]
+    fn lfu_bookkeeping_seed_tie_break() {
+        let ops = [
+            LfuOp::Put(1),
+            LfuOp::Put(2),
+            LfuOp::Get(2),
+            LfuOp::Get(1),
+            LfuOp::Put(3),
+            LfuOp::Get(1),
+            LfuOp::Get(2),
+            LfuOp::PutExpired(2),
+            LfuOp::Put(4),
+            LfuOp::DrainExpired,
+        ];
+        run_lfu_bookkeeping(2, &ops).unwrap();
+    }
+}
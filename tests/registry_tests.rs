@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod registry_tests {
+    use std::time::Duration;
+    use tokio::time::sleep;
+    use rustycache::registry::CacheRegistry;
+    use rustycache::rustycache::Rustycache;
+    use rustycache::strategy::StrategyType;
+
+    #[tokio::test]
+    async fn test_shutdown_all_stops_every_cleaner() {
+        let mut strings: Rustycache<String, String> =
+            Rustycache::new(10, Duration::from_millis(100), Duration::from_millis(200), StrategyType::LRU);
+        let mut numbers: Rustycache<u32, u64> =
+            Rustycache::new(10, Duration::from_millis(100), Duration::from_millis(200), StrategyType::FIFO);
+        let mut counts: Rustycache<u8, usize> =
+            Rustycache::new(10, Duration::from_millis(100), Duration::from_millis(200), StrategyType::LFU);
+
+        let mut registry = CacheRegistry::new();
+        registry.register(&strings);
+        registry.register(&numbers);
+        registry.register(&counts);
+        assert_eq!(registry.len(), 3);
+
+        registry.shutdown_all().await;
+        assert!(registry.is_empty());
+
+        strings.insert("a".to_string(), "A".to_string());
+        numbers.insert(1, 1);
+        counts.insert(1, 1);
+        sleep(Duration::from_millis(600)).await;
+
+        assert_eq!(strings.len(), 1);
+        assert_eq!(numbers.len(), 1);
+        assert_eq!(counts.len(), 1);
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod server_tests {
+    use std::time::Duration;
+    use rustycache::rustycache::Rustycache;
+    use rustycache::server;
+    use rustycache::strategy::StrategyType;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    async fn start() -> (Rustycache<String, String>, TcpStream) {
+        let cache = Rustycache::new(10, Duration::from_secs(60), Duration::from_secs(60), StrategyType::LRU);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(server::serve(listener, cache.clone()));
+        (cache, TcpStream::connect(addr).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_commands_round_trip() {
+        let (mut cache, stream) = start().await;
+        let (reader, mut writer) = stream.into_split();
+        let mut replies = BufReader::new(reader).lines();
+        let mut send = async |command: &str| {
+            writer.write_all(format!("{command}\r\n").as_bytes()).await.unwrap();
+            replies.next_line().await.unwrap().unwrap()
+        };
+
+        assert_eq!(send("SET greeting hello world").await, "OK");
+        assert_eq!(send("GET greeting").await, "hello world");
+        assert_eq!(send("TTL greeting").await, "59");
+        assert_eq!(send("GET missing").await, "(nil)");
+        assert_eq!(send("TTL missing").await, "-2");
+        assert_eq!(send("DEL greeting").await, "1");
+        assert_eq!(send("DEL greeting").await, "0");
+        assert_eq!(send("GET greeting").await, "(nil)");
+
+        cache.put_permanent("config".to_string(), "on".to_string());
+        assert_eq!(send("ttl config").await, "-1");
+
+        assert_eq!(send("SET onlykey").await, "ERR wrong number of arguments for 'SET'");
+        assert_eq!(send("GET a b").await, "ERR wrong number of arguments for 'GET'");
+        assert_eq!(send("PING").await, "ERR unknown command 'PING'");
+        assert_eq!(send("FLUSH all").await, "ERR unknown command 'FLUSH'");
+    }
+}
@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod spillover_tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use rustycache::rustycache::{Rustycache, RustycacheBuilder};
+    use rustycache::strategy::StrategyType;
+
+    fn spill_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rustycache-spillover-{name}-{}", std::process::id()))
+    }
+
+    fn files_in(dir: &PathBuf) -> usize {
+        std::fs::read_dir(dir).map(|entries| entries.count()).unwrap_or(0)
+    }
+
+    #[tokio::test]
+    async fn test_evicted_entry_is_promoted_back_from_disk() {
+        let dir = spill_dir("promote");
+        let mut cache = Rustycache::new(2, Duration::from_secs(60), Duration::from_secs(60), StrategyType::LRU)
+            .with_spillover(&dir, 10)
+            .unwrap();
+        cache.insert("a".to_string(), "A".to_string());
+        cache.insert("b".to_string(), "B".to_string());
+        cache.insert("c".to_string(), "C".to_string());
+
+        assert!(!cache.contains(&"a".to_string()));
+        assert_eq!(files_in(&dir), 1);
+
+        assert_eq!(cache.get(&"a".to_string()), Some("A".to_string()));
+        assert!(cache.contains(&"a".to_string()));
+        assert_eq!(cache.len(), 2);
+        // Promoting "a" pushed "b" out in its place.
+        assert!(!cache.contains(&"b".to_string()));
+        assert_eq!(files_in(&dir), 1);
+        assert_eq!(cache.get(&"b".to_string()), Some("B".to_string()));
+
+        cache.remove(&"c".to_string());
+        cache.clear();
+        assert_eq!(cache.get(&"c".to_string()), None);
+        assert_eq!(files_in(&dir), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_disk_tier_is_bounded() {
+        let dir = spill_dir("bounded");
+        let mut cache = Rustycache::new(1, Duration::from_secs(60), Duration::from_secs(60), StrategyType::FIFO)
+            .with_spillover(&dir, 2)
+            .unwrap();
+        for i in 0..5 {
+            cache.insert(i, i * 10);
+        }
+
+        assert_eq!(files_in(&dir), 2);
+        assert_eq!(cache.get(&0), None);
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&3), Some(30));
+        drop(cache);
+        assert_eq!(files_in(&dir), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_puts_spill_their_victims() {
+        let dir = spill_dir("coalesced");
+        let mut cache = Rustycache::new(1, Duration::from_secs(60), Duration::from_secs(60), StrategyType::LRU)
+            .with_spillover(&dir, 10)
+            .unwrap()
+            .with_put_coalescing(Duration::from_millis(20));
+        cache.insert(1, 10);
+        cache.flush().await;
+        cache.insert(2, 20);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(!cache.contains(&1));
+        assert_eq!(files_in(&dir), 1);
+        assert_eq!(cache.get(&1), Some(10));
+        drop(cache);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_watermark_batch_spills_every_victim() {
+        let dir = spill_dir("watermarks");
+        let mut cache = RustycacheBuilder::new(StrategyType::FIFO)
+            .watermarks(1, 5)
+            .build()
+            .with_spillover(&dir, 10)
+            .unwrap();
+        for i in 0..6 {
+            cache.insert(i, i * 10);
+        }
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(files_in(&dir), 4);
+        for i in 0..4 {
+            assert_eq!(cache.get(&i), Some(i * 10));
+        }
+        drop(cache);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_every_write_path_drops_the_spilled_copy() {
+        use rustycache::strategy::MockClock;
+
+        let dir = spill_dir("stale");
+        let clock = MockClock::new(chrono::Utc::now());
+        let mut cache = RustycacheBuilder::new(StrategyType::LRU)
+            .capacity(1)
+            .without_cleaner()
+            .clock(clock.clone())
+            .build()
+            .with_spillover(&dir, 10)
+            .unwrap();
+        cache.insert("k".to_string(), "v1".to_string());
+        cache.insert("x".to_string(), "x".to_string());
+        assert_eq!(files_in(&dir), 1);
+
+        cache.put_with_ttl("k".to_string(), "v2".to_string(), Duration::from_millis(50));
+        clock.advance_time(Duration::from_millis(100));
+        assert_eq!(cache.get(&"k".to_string()), None);
+
+        cache.put_permanent("p".to_string(), "p".to_string());
+        assert!(!cache.put_if_absent_with_ttl("p".to_string(), "q".to_string(), Duration::from_secs(1)));
+        assert_eq!(cache.get_or_insert("x".to_string(), "fresh".to_string()), "fresh");
+        cache.remove(&"x".to_string());
+        assert_eq!(cache.get(&"x".to_string()), None);
+        assert_eq!(cache.get(&"p".to_string()), Some("p".to_string()));
+        drop(cache);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_spilled_entries_age_with_the_cache_clock() {
+        use rustycache::strategy::MockClock;
+
+        let dir = spill_dir("clock");
+        let clock = MockClock::new(chrono::Utc::now());
+        let mut cache = RustycacheBuilder::new(StrategyType::FIFO)
+            .capacity(1)
+            .ttl(Duration::from_secs(60))
+            .without_cleaner()
+            .clock(clock.clone())
+            .build()
+            .with_spillover(&dir, 10)
+            .unwrap();
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        assert_eq!(files_in(&dir), 1);
+
+        clock.advance_time(Duration::from_secs(61));
+        assert_eq!(cache.get(&1), None);
+        drop(cache);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}